@@ -0,0 +1,226 @@
+//! Classification of the outer and inner attributes on an item, and
+//! extraction of intra-doc links from doc comments.
+
+use std::collections::HashSet;
+
+use syn::{punctuated::Punctuated, Attribute, Expr, ExprLit, Lit, Meta, MetaList, Path, Token};
+
+/// The attributes on a single item, sorted by what the indexer does with
+/// them.
+#[derive(Default)]
+pub struct ItemAttributes<'a> {
+    /// Names of the traits in `#[derive(...)]`, e.g. `Debug` or `Serialize`.
+    pub derives: Vec<String>,
+    /// Whether the item is marked as a test, e.g. `#[test]` or
+    /// `#[tokio::test]`.
+    pub is_test: bool,
+    /// The `#[cfg(...)]` attributes, whose tokens are the predicate.
+    pub cfgs: Vec<&'a MetaList>,
+    /// Every other attribute, with its path as written, e.g. `tokio::main`.
+    pub other: Vec<(String, &'a Meta)>,
+    /// The text of the item's `///` or `//!` doc comments, one line per
+    /// comment line.
+    pub doc: Option<String>,
+}
+
+/// Attributes that are either modelled elsewhere or carry no meaning of
+/// their own in the graph.
+const SKIPPED: &[&str] = &["path", "macro_export"];
+
+/// Sorts `attrs` into derives, test markers, `cfg` predicates, and the rest.
+pub fn classify(attrs: &[Attribute]) -> ItemAttributes<'_> {
+    let mut classified = ItemAttributes::default();
+    for attr in attrs {
+        let path = path_text(attr.path());
+        match (&attr.meta, path.as_str()) {
+            (Meta::List(_), "derive") => {
+                let traits = attr
+                    .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                    .unwrap_or_default();
+                classified.derives.extend(
+                    traits
+                        .iter()
+                        .filter_map(|path| path.segments.last())
+                        .map(|segment| segment.ident.to_string()),
+                );
+            }
+            (Meta::List(list), "cfg") => classified.cfgs.push(list),
+            (Meta::NameValue(name_value), "doc") => {
+                if let Expr::Lit(ExprLit {
+                    lit: Lit::Str(text),
+                    ..
+                }) = &name_value.value
+                {
+                    let doc = classified.doc.get_or_insert_with(String::new);
+                    for line in text.value().lines() {
+                        if !doc.is_empty() {
+                            doc.push('\n');
+                        }
+                        // `/// text` is stored as " text".
+                        doc.push_str(line.strip_prefix(' ').unwrap_or(line));
+                    }
+                }
+            }
+            (_, path) if path == "test" || path.ends_with("::test") => {
+                classified.is_test = true;
+            }
+            (_, path) if SKIPPED.contains(&path) => {}
+            (meta, _) => classified.other.push((path, meta)),
+        }
+    }
+    classified
+}
+
+/// Renders an attribute path such as `tokio::main`.
+fn path_text(path: &Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Returns the targets of the intra-doc links in `doc`, such as `User` for
+/// ``[`User`]``, `crate::utils::helper` for `[helper](crate::utils::helper)`,
+/// or `Shape::area` for ``[`area`][Shape::area]``.
+///
+/// Disambiguators like `struct@` and suffixes like `()` and `!` are
+/// removed. Links to URLs and anything inside code blocks are ignored.
+pub fn doc_links(doc: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in doc.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            lines.push(line);
+        }
+    }
+
+    // Reference definitions, e.g. `[user]: crate::User`. Links that use
+    // their label are reported once, through the definition.
+    let mut labels = HashSet::new();
+    let mut links = Vec::new();
+    for line in &lines {
+        let definition = line
+            .trim_start()
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]:"));
+        if let Some((label, target)) = definition {
+            labels.insert(label.to_lowercase());
+            links.extend(link_path(target));
+        }
+    }
+    for line in &lines {
+        if !line.trim_start().starts_with('[') || !line.contains("]:") {
+            links.extend(inline_links(line, &labels));
+        }
+    }
+    links
+}
+
+/// Finds the link targets in a single line of Markdown.
+fn inline_links(line: &str, labels: &HashSet<String>) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(['[', '`']) {
+        rest = &rest[start..];
+        if let Some(code) = rest.strip_prefix('`') {
+            // Brackets inside inline code, e.g. `v[0]`, are not links.
+            rest = code.split_once('`').map_or("", |(_, after)| after);
+            continue;
+        }
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let text = &rest[1..end];
+        let after = &rest[end + 1..];
+        let (target, remaining) = if let Some(inline) = after.strip_prefix('(') {
+            match inline.split_once(')') {
+                Some((target, remaining)) => (target, remaining),
+                None => (text, after),
+            }
+        } else if let Some(reference) = after.strip_prefix('[') {
+            match reference.split_once(']') {
+                Some((target, remaining)) => (target, remaining),
+                None => (text, after),
+            }
+        } else {
+            (text, after)
+        };
+        if !labels.contains(&target.to_lowercase()) {
+            links.extend(link_path(target));
+        }
+        rest = remaining;
+    }
+    links
+}
+
+/// Turns a link target into the path it names, or `None` if it is not a
+/// Rust path, e.g. a URL.
+fn link_path(target: &str) -> Option<String> {
+    let target = target.trim().trim_matches('`');
+    let target = target.split_once('@').map_or(target, |(_, path)| path);
+    let target = target
+        .strip_suffix("()")
+        .or_else(|| target.strip_suffix('!'))
+        .unwrap_or(target);
+    let is_path = target.split("::").all(|segment| {
+        segment.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+    });
+    is_path.then(|| target.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_inline_reference_and_shortcut_links() {
+        let doc = "See [`User`], [new](User::new), and [`area`][Shape::area].";
+        assert_eq!(doc_links(doc), ["User", "User::new", "Shape::area"]);
+    }
+
+    #[test]
+    fn resolves_reference_definitions_once() {
+        let doc = "Uses [the helper].\n\n[the helper]: crate::utils::helper";
+        assert_eq!(doc_links(doc), ["crate::utils::helper"]);
+    }
+
+    #[test]
+    fn strips_disambiguators_and_suffixes() {
+        let doc = "[struct@User], [`helper()`], and [`log!`]";
+        assert_eq!(doc_links(doc), ["User", "helper", "log"]);
+    }
+
+    #[test]
+    fn ignores_urls_code_blocks_and_inline_code() {
+        let doc = "[docs](https://docs.rs) and `v[0]`\n```\nlet x = [User];\n```";
+        assert!(doc_links(doc).is_empty());
+    }
+
+    #[test]
+    fn classifies_doc_cfg_test_and_derives() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            /// Adds one.
+            #[cfg(feature = "extra")]
+            #[tokio::test]
+            #[instrument(level = "debug")]
+            fn add_one() {}
+        };
+        let attributes = classify(&item.attrs);
+        assert_eq!(attributes.doc.as_deref(), Some("Adds one."));
+        assert_eq!(attributes.cfgs.len(), 1);
+        assert!(attributes.is_test);
+        assert_eq!(attributes.other.len(), 1);
+        assert_eq!(attributes.other[0].0, "instrument");
+
+        let item: syn::ItemStruct = syn::parse_quote! {
+            #[derive(Debug, serde::Serialize)]
+            struct User;
+        };
+        assert_eq!(classify(&item.attrs).derives, ["Debug", "Serialize"]);
+    }
+}
@@ -0,0 +1,664 @@
+//! Discovery of crate roots and resolution of out-of-line `mod` declarations.
+//!
+//! Starting from each crate root, `mod foo;` declarations are followed to the
+//! files rustc would load for them, so every indexed file knows which module
+//! it defines.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use cargo_metadata::{Metadata, MetadataCommand};
+use syn::{Attribute, Expr, ExprLit, Item, Lit, Meta};
+use walkdir::WalkDir;
+
+/// The kind of Cargo target a crate root belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    Lib,
+    ProcMacro,
+    Bin,
+    Example,
+    Test,
+    Bench,
+}
+
+impl TargetKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetKind::Lib => "lib",
+            TargetKind::ProcMacro => "proc-macro",
+            TargetKind::Bin => "bin",
+            TargetKind::Example => "example",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+        }
+    }
+
+    /// Whether the target is the package's library, which other crates
+    /// refer to by name.
+    pub fn is_library(self) -> bool {
+        matches!(self, TargetKind::Lib | TargetKind::ProcMacro)
+    }
+
+    /// Maps the kinds Cargo reports for a target, returning `None` for
+    /// build scripts and anything else that is not indexed.
+    fn from_cargo(kinds: &[cargo_metadata::TargetKind]) -> Option<Self> {
+        use cargo_metadata::TargetKind as Cargo;
+        kinds.iter().find_map(|kind| match kind {
+            Cargo::ProcMacro => Some(TargetKind::ProcMacro),
+            Cargo::Lib | Cargo::RLib | Cargo::DyLib | Cargo::CDyLib | Cargo::StaticLib => {
+                Some(TargetKind::Lib)
+            }
+            Cargo::Bin => Some(TargetKind::Bin),
+            Cargo::Example => Some(TargetKind::Example),
+            Cargo::Test => Some(TargetKind::Test),
+            Cargo::Bench => Some(TargetKind::Bench),
+            _ => None,
+        })
+    }
+}
+
+/// The package a crate root belongs to.
+#[derive(Clone, Debug)]
+pub struct Package {
+    pub name: String,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+}
+
+/// The root file of a single crate, e.g. `src/lib.rs` or `src/bin/tool.rs`.
+pub struct CrateRoot {
+    /// Name used as the first segment of every module path in this crate.
+    pub name: String,
+    /// Name of the target, before any suffix added to keep `name` unique.
+    pub target: String,
+    pub kind: TargetKind,
+    pub path: PathBuf,
+    /// The package the target belongs to.
+    pub package: Package,
+}
+
+/// A source file reached from a crate root, along with the module it defines.
+pub struct SourceFile {
+    pub path: PathBuf,
+    /// Name of the crate whose root reaches this file.
+    pub crate_name: String,
+    /// Kind of the target that crate belongs to.
+    pub crate_kind: TargetKind,
+    /// Path of the module this file defines, e.g. `my_crate::utils`.
+    pub module_path: String,
+    /// Path of the module that declared this file with `mod`, if any.
+    pub parent_module: Option<String>,
+    pub ast: syn::File,
+}
+
+/// A `mod foo;` declaration waiting to be resolved to a file.
+struct ModDecl {
+    name: String,
+    module_path: String,
+    parent_module: String,
+    /// The `#[path = "..."]` override, already joined onto its base directory.
+    path_override: Option<PathBuf>,
+    /// Directory in which `foo.rs` or `foo/mod.rs` is looked up.
+    dir: PathBuf,
+}
+
+/// Finds every crate root below `project_root`.
+///
+/// Packages and their targets are read with `cargo metadata`, so workspace
+/// members, `exclude`, inherited fields and target auto-discovery follow
+/// Cargo itself. When `project_root` has a `Cargo.toml`, its package and, for
+/// a workspace, its members below `project_root` are indexed. Otherwise every
+/// `Cargo.toml` below it is read. When a name is used by more than one crate,
+/// the non-library crates are suffixed with their kind, e.g.
+/// `my_crate(bin)`, and crates whose names still collide, such as the
+/// `tests/integration.rs` of two members, also with their package, e.g.
+/// `integration(app test)`.
+pub fn find_crate_roots(project_root: &Path) -> Vec<CrateRoot> {
+    let root_manifest = project_root.join("Cargo.toml");
+    let manifests: Vec<PathBuf> = if root_manifest.is_file() {
+        vec![root_manifest]
+    } else {
+        WalkDir::new(project_root)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != "target" && e.file_name() != ".git")
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name() == "Cargo.toml")
+            .map(|e| e.into_path())
+            .collect()
+    };
+    let project_dir = project_root
+        .canonicalize()
+        .unwrap_or_else(|_| project_root.to_path_buf());
+    let mut seen = HashSet::new();
+    let mut roots = Vec::new();
+    for manifest in manifests {
+        let Some(metadata) = read_metadata(&manifest) else {
+            continue;
+        };
+        for package in metadata.workspace_packages() {
+            // A member passed as `project_root` still reports its whole
+            // workspace, and members are reached again from the walk.
+            let inside = package.manifest_path.starts_with(&project_dir);
+            if inside && seen.insert(package.manifest_path.clone()) {
+                roots.extend(package_targets(package));
+            }
+        }
+    }
+    if roots.is_empty() {
+        // Not a Cargo project; fall back to the default layout in place.
+        let package = Package {
+            name: directory_name(project_root),
+            version: None,
+            edition: None,
+        };
+        roots = default_targets(project_root, package);
+    }
+
+    rename_shared(&mut roots, |root| {
+        (!root.kind.is_library()).then(|| format!("{}({})", root.target, root.kind.as_str()))
+    });
+    rename_shared(&mut roots, |root| {
+        Some(format!(
+            "{}({} {})",
+            root.target,
+            root.package.name,
+            root.kind.as_str()
+        ))
+    });
+    roots
+}
+
+/// Renames each root whose name is shared with another root, if `rename`
+/// gives it a new one.
+fn rename_shared(roots: &mut [CrateRoot], rename: impl Fn(&CrateRoot) -> Option<String>) {
+    let names: Vec<String> = roots.iter().map(|r| r.name.clone()).collect();
+    for root in roots {
+        let shared = names.iter().filter(|n| **n == root.name).count() > 1;
+        if shared {
+            if let Some(name) = rename(root) {
+                root.name = name;
+            }
+        }
+    }
+}
+
+/// Runs `cargo metadata` on the manifest at `path`, reporting it if Cargo
+/// rejects it.
+fn read_metadata(path: &Path) -> Option<Metadata> {
+    match MetadataCommand::new().manifest_path(path).no_deps().exec() {
+        Ok(metadata) => Some(metadata),
+        Err(err) => {
+            eprintln!("⚠️  Skipping {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Returns the crate roots of the targets Cargo reports for `package`.
+fn package_targets(package: &cargo_metadata::Package) -> Vec<CrateRoot> {
+    let info = Package {
+        name: package.name.to_string(),
+        version: Some(package.version.to_string()),
+        edition: Some(package.edition.as_str().to_string()),
+    };
+    package
+        .targets
+        .iter()
+        .filter_map(|target| {
+            let kind = TargetKind::from_cargo(&target.kind)?;
+            let name = target.name.replace('-', "_");
+            Some(CrateRoot {
+                name: name.clone(),
+                target: name,
+                kind,
+                path: target.src_path.clone().into_std_path_buf(),
+                package: info.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Discovers the targets of Cargo's default layout in `package_dir`, for
+/// directories without a manifest.
+fn default_targets(package_dir: &Path, package: Package) -> Vec<CrateRoot> {
+    let src = package_dir.join("src");
+    let mut targets = Vec::new();
+    let lib = src.join("lib.rs");
+    if lib.is_file() {
+        targets.push((package.name.clone(), TargetKind::Lib, lib));
+    }
+    let main = src.join("main.rs");
+    if main.is_file() {
+        targets.push((package.name.clone(), TargetKind::Bin, main));
+    }
+    for (dir, kind) in [
+        (src.join("bin"), TargetKind::Bin),
+        (package_dir.join("examples"), TargetKind::Example),
+        (package_dir.join("tests"), TargetKind::Test),
+        (package_dir.join("benches"), TargetKind::Bench),
+    ] {
+        for (name, path) in auto_targets(&dir) {
+            targets.push((name, kind, path));
+        }
+    }
+    targets
+        .into_iter()
+        .map(|(name, kind, path)| CrateRoot {
+            name: name.clone(),
+            target: name,
+            kind,
+            path,
+            package: package.clone(),
+        })
+        .collect()
+}
+
+/// Returns the name of `dir` as a crate name.
+fn directory_name(dir: &Path) -> String {
+    dir.canonicalize()
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .map(|n| n.to_string_lossy().replace('-', "_"))
+        })
+        .unwrap_or_else(|| "crate".to_string())
+}
+
+/// Discovers targets in an auto-discovery directory such as `src/bin`,
+/// where each `name.rs` or `name/main.rs` is its own crate.
+fn auto_targets(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let root = if path.is_dir() {
+                path.join("main.rs")
+            } else {
+                path.clone()
+            };
+            let is_rs = root.extension().is_some_and(|ext| ext == "rs");
+            if !is_rs || !root.is_file() {
+                return None;
+            }
+            let name = path.file_stem()?.to_string_lossy().replace('-', "_");
+            Some((name, root))
+        })
+        .collect()
+}
+
+/// Loads every file belonging to the crate, following `mod` declarations
+/// from the crate root. Files are returned parents-first.
+///
+/// Files that cannot be read or parsed are reported and skipped, along with
+/// any modules they would have declared.
+pub fn load_crate(root: &CrateRoot) -> Vec<SourceFile> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    let root_dir = root.path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+    let mut pending = vec![(root.path.clone(), root.name.clone(), None, root_dir)];
+    while let Some((path, module_path, parent_module, dir)) = pending.pop() {
+        if !visited.insert(path.canonicalize().unwrap_or_else(|_| path.clone())) {
+            continue;
+        }
+        let ast = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|code| syn::parse_file(&code).map_err(anyhow::Error::from))
+        {
+            Ok(ast) => ast,
+            Err(err) => {
+                eprintln!("⚠️  Skipping {}: {}", path.display(), err);
+                continue;
+            }
+        };
+
+        let file_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let mut decls = Vec::new();
+        collect_mod_decls(&ast.items, &module_path, &dir, Some(&file_dir), &mut decls);
+        // Pushed in reverse so that modules are visited in declaration order.
+        for decl in decls.into_iter().rev() {
+            match resolve_mod_decl(&decl) {
+                Some((child, child_dir)) => {
+                    pending.push((child, decl.module_path, Some(decl.parent_module), child_dir))
+                }
+                None => eprintln!(
+                    "⚠️  Could not find the file for `mod {};` in {}",
+                    decl.name,
+                    path.display()
+                ),
+            }
+        }
+
+        files.push(SourceFile {
+            path,
+            crate_name: root.name.clone(),
+            crate_kind: root.kind,
+            module_path,
+            parent_module,
+            ast,
+        });
+    }
+    files
+}
+
+/// Collects the out-of-line `mod` declarations in `items`, descending into
+/// inline modules.
+///
+/// `dir` is where child module files are looked up. `file_dir` is the
+/// directory of the current file, which `#[path]` attributes are relative to
+/// outside of inline modules; it is `None` once inside one.
+fn collect_mod_decls(
+    items: &[Item],
+    module_path: &str,
+    dir: &Path,
+    file_dir: Option<&Path>,
+    decls: &mut Vec<ModDecl>,
+) {
+    for item in items {
+        let Item::Mod(item_mod) = item else {
+            continue;
+        };
+        let name = item_mod.ident.to_string();
+        let child_path = format!("{}::{}", module_path, name);
+        let path_attr = path_attribute(&item_mod.attrs);
+        match &item_mod.content {
+            Some((_, content)) => {
+                let child_dir = dir.join(path_attr.unwrap_or(name));
+                collect_mod_decls(content, &child_path, &child_dir, None, decls);
+            }
+            None => decls.push(ModDecl {
+                path_override: path_attr.map(|p| file_dir.unwrap_or(dir).join(p)),
+                name,
+                module_path: child_path,
+                parent_module: module_path.to_string(),
+                dir: dir.to_path_buf(),
+            }),
+        }
+    }
+}
+
+/// Resolves a `mod` declaration to its file and the directory in which that
+/// file's own child modules live.
+fn resolve_mod_decl(decl: &ModDecl) -> Option<(PathBuf, PathBuf)> {
+    if let Some(path) = &decl.path_override {
+        // Files loaded through `#[path]` own the directory they are in.
+        let dir = path.parent()?.to_path_buf();
+        return path.is_file().then(|| (path.clone(), dir));
+    }
+    let flat = decl.dir.join(format!("{}.rs", decl.name));
+    let nested = decl.dir.join(&decl.name).join("mod.rs");
+    if flat.is_file() {
+        Some((flat, decl.dir.join(&decl.name)))
+    } else if nested.is_file() {
+        Some((nested, decl.dir.join(&decl.name)))
+    } else {
+        None
+    }
+}
+
+/// Returns the value of a `#[path = "..."]` attribute, if present.
+fn path_attribute(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(nv) if nv.path.is_ident("path") => match &nv.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(s), ..
+            }) => Some(s.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory under the system's temp dir, removed when dropped.
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        /// Creates `files`, given as relative path and contents, in a fresh
+        /// directory named after `name`.
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("rust-indexer-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            for (path, contents) in files {
+                let path = dir.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, contents).unwrap();
+            }
+            TempTree(dir)
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Returns the sorted names of the crates found in `tree`.
+    fn crate_names(tree: &TempTree) -> Vec<String> {
+        let mut names: Vec<String> = find_crate_roots(&tree.0)
+            .into_iter()
+            .map(|root| root.name)
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn reads_workspace_members_through_cargo() {
+        let tree = TempTree::new(
+            "members",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n\n\
+                     [workspace.package]\nversion = \"1.2.0\"\nedition = \"2021\"\n",
+                ),
+                (
+                    "crates/core/Cargo.toml",
+                    "[package]\nname = \"core-lib\"\nversion.workspace = true\n\
+                     edition.workspace = true\n\n[lib]\nname = \"engine\"\n",
+                ),
+                ("crates/core/src/lib.rs", ""),
+                ("crates/core/src/bin/tool.rs", ""),
+                ("crates/core/build.rs", "fn main() {}"),
+                (
+                    "crates/old/Cargo.toml",
+                    "[package]\nname = \"old\"\nversion = \"0.1.0\"\n",
+                ),
+                ("crates/old/src/lib.rs", ""),
+            ],
+        );
+        let roots = find_crate_roots(&tree.0);
+        let crates: Vec<(&str, TargetKind)> = roots
+            .iter()
+            .map(|root| (root.name.as_str(), root.kind))
+            .collect();
+        assert_eq!(
+            crates,
+            [("engine", TargetKind::Lib), ("tool", TargetKind::Bin)]
+        );
+        let package = &roots[0].package;
+        assert_eq!(package.name, "core-lib");
+        assert_eq!(package.version.as_deref(), Some("1.2.0"));
+        assert_eq!(package.edition.as_deref(), Some("2021"));
+    }
+
+    #[test]
+    fn falls_back_to_the_default_layout_without_a_manifest() {
+        let tree = TempTree::new(
+            "layout",
+            &[
+                ("src/lib.rs", ""),
+                ("src/main.rs", ""),
+                ("src/bin/tool/main.rs", ""),
+                ("tests/smoke.rs", ""),
+            ],
+        );
+        let names = crate_names(&tree);
+        let dir = directory_name(&tree.0);
+        assert_eq!(
+            names,
+            [
+                dir.clone(),
+                format!("{}(bin)", dir),
+                "smoke".to_string(),
+                "tool".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_crate_names_unique_across_packages() {
+        let tree = TempTree::new(
+            "names",
+            &[
+                ("Cargo.toml", "[workspace]\nmembers = [\"app\", \"lib\"]\n"),
+                (
+                    "app/Cargo.toml",
+                    "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+                ),
+                ("app/src/lib.rs", ""),
+                ("app/src/main.rs", ""),
+                ("app/tests/integration.rs", ""),
+                (
+                    "lib/Cargo.toml",
+                    "[package]\nname = \"lib\"\nversion = \"0.1.0\"\n",
+                ),
+                ("lib/src/lib.rs", ""),
+                ("lib/tests/integration.rs", ""),
+                ("lib/tests/unique.rs", ""),
+            ],
+        );
+        assert_eq!(
+            crate_names(&tree),
+            [
+                "app",
+                "app(bin)",
+                "integration(app test)",
+                "integration(lib test)",
+                "lib",
+                "unique",
+            ]
+        );
+    }
+
+    /// Loads the crate rooted at `src/lib.rs` in `tree` and returns each
+    /// file's module path with its path relative to `src`, sorted.
+    fn loaded_modules(tree: &TempTree) -> Vec<(String, String)> {
+        let root = CrateRoot {
+            name: "c".to_string(),
+            target: "c".to_string(),
+            kind: TargetKind::Lib,
+            path: tree.0.join("src/lib.rs"),
+            package: Package {
+                name: "c".to_string(),
+                version: None,
+                edition: None,
+            },
+        };
+        let mut modules: Vec<(String, String)> = load_crate(&root)
+            .into_iter()
+            .map(|file| {
+                let path = file.path.strip_prefix(tree.0.join("src")).unwrap();
+                (file.module_path, path.to_string_lossy().replace('\\', "/"))
+            })
+            .collect();
+        modules.sort();
+        modules
+    }
+
+    fn modules(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(module, path)| (module.to_string(), path.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_flat_and_mod_rs_files_with_their_children() {
+        let tree = TempTree::new(
+            "layouts",
+            &[
+                ("src/lib.rs", "mod flat; mod nested;"),
+                ("src/flat.rs", "mod child;"),
+                ("src/flat/child.rs", ""),
+                ("src/nested/mod.rs", "mod inner;"),
+                ("src/nested/inner.rs", ""),
+            ],
+        );
+        assert_eq!(
+            loaded_modules(&tree),
+            modules(&[
+                ("c", "lib.rs"),
+                ("c::flat", "flat.rs"),
+                ("c::flat::child", "flat/child.rs"),
+                ("c::nested", "nested/mod.rs"),
+                ("c::nested::inner", "nested/inner.rs"),
+            ])
+        );
+    }
+
+    #[test]
+    fn resolves_path_attributes_from_the_file_or_inline_module() {
+        let tree = TempTree::new(
+            "paths",
+            &[
+                (
+                    "src/lib.rs",
+                    r#"
+                    #[path = "other/thing.rs"]
+                    mod thing;
+                    mod inline {
+                        #[path = "renamed.rs"]
+                        mod target;
+                        mod plain;
+                    }
+                    "#,
+                ),
+                ("src/other/thing.rs", "mod sub;"),
+                ("src/other/sub.rs", ""),
+                ("src/inline/renamed.rs", ""),
+                ("src/inline/plain.rs", ""),
+            ],
+        );
+        assert_eq!(
+            loaded_modules(&tree),
+            modules(&[
+                ("c", "lib.rs"),
+                ("c::inline::plain", "inline/plain.rs"),
+                ("c::inline::target", "inline/renamed.rs"),
+                // Files loaded through `#[path]` own their directory.
+                ("c::thing", "other/thing.rs"),
+                ("c::thing::sub", "other/sub.rs"),
+            ])
+        );
+    }
+
+    #[test]
+    fn skips_modules_whose_file_is_missing() {
+        let tree = TempTree::new(
+            "missing",
+            &[
+                ("src/lib.rs", "mod missing; mod present;"),
+                ("src/present.rs", ""),
+            ],
+        );
+        assert_eq!(
+            loaded_modules(&tree),
+            modules(&[("c", "lib.rs"), ("c::present", "present.rs")])
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Flattening of `use` declarations into individual imports.
+
+use syn::UseTree;
+
+/// A single name brought into scope by a `use` declaration.
+///
+/// `use a::{b, c as d, e::*};` becomes three imports: `a::b`, `a::c` aliased
+/// to `d`, and a glob import of `a::e`.
+#[derive(Clone)]
+pub struct Import {
+    /// The imported path as written, e.g. `["crate", "utils", "helper"]`.
+    pub segments: Vec<String>,
+    /// The name given with `as`, if the import is renamed.
+    pub alias: Option<String>,
+    /// Whether this is a `*` import of everything in `segments`.
+    pub glob: bool,
+}
+
+impl Import {
+    /// The name the import binds, i.e. its alias or last segment. Globs
+    /// bind no single name.
+    pub fn name(&self) -> Option<&str> {
+        if self.glob {
+            return None;
+        }
+        self.alias
+            .as_ref()
+            .or(self.segments.last())
+            .map(String::as_str)
+    }
+}
+
+/// Flattens a `use` tree into the imports it declares.
+pub fn flatten_use_tree(tree: &UseTree) -> Vec<Import> {
+    let mut imports = Vec::new();
+    flatten_into(tree, &mut Vec::new(), &mut imports);
+    imports
+}
+
+fn flatten_into(tree: &UseTree, prefix: &mut Vec<String>, imports: &mut Vec<Import>) {
+    match tree {
+        UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            flatten_into(&use_path.tree, prefix, imports);
+            prefix.pop();
+        }
+        UseTree::Name(use_name) => imports.push(Import {
+            segments: joined(prefix, &use_name.ident),
+            alias: None,
+            glob: false,
+        }),
+        UseTree::Rename(use_rename) => imports.push(Import {
+            segments: joined(prefix, &use_rename.ident),
+            alias: Some(use_rename.rename.to_string()),
+            glob: false,
+        }),
+        UseTree::Glob(_) => imports.push(Import {
+            segments: prefix.clone(),
+            alias: None,
+            glob: true,
+        }),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_into(tree, prefix, imports);
+            }
+        }
+    }
+}
+
+/// Appends `ident` to `prefix`, except that `a::{self}` imports `a` itself.
+fn joined(prefix: &[String], ident: &syn::Ident) -> Vec<String> {
+    let mut segments = prefix.to_vec();
+    if ident != "self" || segments.is_empty() {
+        segments.push(ident.to_string());
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The imports of `tree` as `(path, alias, glob)`.
+    fn flattened(tree: UseTree) -> Vec<(String, Option<String>, bool)> {
+        flatten_use_tree(&tree)
+            .into_iter()
+            .map(|import| (import.segments.join("::"), import.alias, import.glob))
+            .collect()
+    }
+
+    #[test]
+    fn flattens_groups_with_self_renames_and_globs() {
+        assert_eq!(
+            flattened(syn::parse_quote!(a::{self, b as c, d::*})),
+            [
+                ("a".to_string(), None, false),
+                ("a::b".to_string(), Some("c".to_string()), false),
+                ("a::d".to_string(), None, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_a_leading_self_in_a_group() {
+        assert_eq!(
+            flattened(syn::parse_quote!(self::{run, util::{self as u}})),
+            [
+                ("self::run".to_string(), None, false),
+                ("self::util".to_string(), Some("u".to_string()), false),
+            ]
+        );
+        assert_eq!(
+            flattened(syn::parse_quote!({ self, x })),
+            [
+                ("self".to_string(), None, false),
+                ("x".to_string(), None, false)
+            ]
+        );
+    }
+}
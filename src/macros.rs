@@ -0,0 +1,176 @@
+//! Best-effort parsing of macro invocation arguments into expressions and
+//! patterns.
+//!
+//! Macro bodies are opaque token streams to `syn`, so calls inside
+//! `println!("{}", helper())` are not part of the syntax tree. Most macros
+//! take comma-separated expressions, and a few well-known ones have a syntax
+//! of their own; anything else is left unparsed.
+
+use syn::{
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+    Expr, Macro, Pat, Token,
+};
+
+/// The expressions and patterns found in a macro's arguments.
+#[derive(Default)]
+pub struct MacroArguments {
+    pub exprs: Vec<Expr>,
+    /// Patterns, as in `matches!(shape, Shape::Circle(_))`.
+    pub patterns: Vec<Pat>,
+}
+
+/// Parses the arguments of `mac` into the expressions and patterns they
+/// contain.
+///
+/// Named arguments of formatting macros, like `name = expr`, yield just the
+/// value. Returns nothing when the body cannot be parsed.
+pub fn macro_arguments(mac: &Macro) -> MacroArguments {
+    let name = mac
+        .path
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default();
+    let parsed = match name.as_str() {
+        "select" => parse_select.parse2(mac.tokens.clone()),
+        "matches" | "assert_matches" | "debug_assert_matches" => {
+            parse_matches.parse2(mac.tokens.clone())
+        }
+        "vec" => comma_separated
+            .parse2(mac.tokens.clone())
+            .or_else(|_| parse_repeat.parse2(mac.tokens.clone())),
+        _ => comma_separated.parse2(mac.tokens.clone()),
+    };
+    let mut arguments = parsed.unwrap_or_default();
+    arguments.exprs = arguments
+        .exprs
+        .into_iter()
+        .map(|expr| match expr {
+            Expr::Assign(assign) if is_ident(&assign.left) => *assign.right,
+            expr => expr,
+        })
+        .collect();
+    arguments
+}
+
+/// Whether `expr` is a lone identifier, like the name of a named argument.
+fn is_ident(expr: &Expr) -> bool {
+    matches!(expr, Expr::Path(path) if path.path.get_ident().is_some())
+}
+
+/// `a, b, c`, as taken by `format!`, `assert_eq!`, `join!` and most others.
+fn comma_separated(input: ParseStream) -> syn::Result<MacroArguments> {
+    let exprs = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
+    Ok(MacroArguments {
+        exprs: exprs.into_iter().collect(),
+        patterns: Vec::new(),
+    })
+}
+
+/// `elem; count`, as in `vec![0; n]`.
+fn parse_repeat(input: ParseStream) -> syn::Result<MacroArguments> {
+    let elem: Expr = input.parse()?;
+    input.parse::<Token![;]>()?;
+    let count: Expr = input.parse()?;
+    Ok(MacroArguments {
+        exprs: vec![elem, count],
+        patterns: Vec::new(),
+    })
+}
+
+/// `expr, pattern (if guard)?`, as in `matches!(shape, Shape::Circle(_))`.
+fn parse_matches(input: ParseStream) -> syn::Result<MacroArguments> {
+    let mut arguments = MacroArguments::default();
+    arguments.exprs.push(input.parse()?);
+    input.parse::<Token![,]>()?;
+    arguments
+        .patterns
+        .push(Pat::parse_multi_with_leading_vert(input)?);
+    if input.parse::<Option<Token![if]>>()?.is_some() {
+        arguments.exprs.push(input.parse()?);
+    }
+    input.parse::<Option<Token![,]>>()?;
+    Ok(arguments)
+}
+
+/// The branches of `tokio::select!` and `futures::select!`:
+/// `pattern = future (, if condition)? => handler,` and `else => handler`,
+/// optionally preceded by `biased;`.
+fn parse_select(input: ParseStream) -> syn::Result<MacroArguments> {
+    let mut arguments = MacroArguments::default();
+    if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "biased" {
+        input.parse::<syn::Ident>()?;
+        input.parse::<Token![;]>()?;
+    }
+    while !input.is_empty() {
+        if input.parse::<Option<Token![else]>>()?.is_none() {
+            arguments
+                .patterns
+                .push(Pat::parse_multi_with_leading_vert(input)?);
+            input.parse::<Token![=]>()?;
+            arguments.exprs.push(input.parse()?);
+            if input.peek(Token![,]) && input.peek2(Token![if]) {
+                input.parse::<Token![,]>()?;
+                input.parse::<Token![if]>()?;
+                arguments.exprs.push(input.parse()?);
+            }
+        }
+        input.parse::<Token![=>]>()?;
+        arguments.exprs.push(input.parse()?);
+        input.parse::<Option<Token![,]>>()?;
+    }
+    Ok(arguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(mac: Macro) -> (usize, usize) {
+        let arguments = macro_arguments(&mac);
+        (arguments.exprs.len(), arguments.patterns.len())
+    }
+
+    #[test]
+    fn parses_comma_separated_arguments() {
+        assert_eq!(
+            parsed(syn::parse_quote!(println!("{} {}", a(), b()))),
+            (3, 0)
+        );
+        assert_eq!(parsed(syn::parse_quote!(assert_eq!(a(), 1,))), (2, 0));
+    }
+
+    #[test]
+    fn keeps_values_of_named_arguments() {
+        let mac: Macro = syn::parse_quote!(format!("{name}", name = user.name()));
+        let arguments = macro_arguments(&mac);
+        assert!(matches!(arguments.exprs[1], Expr::MethodCall(_)));
+    }
+
+    #[test]
+    fn parses_repeated_vec_elements() {
+        assert_eq!(parsed(syn::parse_quote!(vec![make(); count()])), (2, 0));
+    }
+
+    #[test]
+    fn parses_matches_patterns_and_guards() {
+        let mac: Macro = syn::parse_quote!(matches!(shape, Shape::Circle(r) if r > 1.0));
+        assert_eq!(parsed(mac), (2, 1));
+    }
+
+    #[test]
+    fn parses_select_branches() {
+        let mac: Macro = syn::parse_quote!(select! {
+            biased;
+            value = recv(), if ready() => handle(value),
+            else => idle(),
+        });
+        assert_eq!(parsed(mac), (4, 1));
+    }
+
+    #[test]
+    fn leaves_unknown_syntax_unparsed() {
+        assert_eq!(parsed(syn::parse_quote!(custom!(a => b; c))), (0, 0));
+    }
+}
@@ -1,16 +1,41 @@
-use std::{fs, path::PathBuf};
+mod attributes;
+mod crate_tree;
+mod imports;
+mod macros;
+mod symbols;
+
 use anyhow::{Context, Result};
 use clap::Parser;
 use neo4rs::*;
-use syn::{Expr, ExprCall, ExprPath, ExprStruct, Item, Stmt};
-use walkdir::WalkDir;
+use quote::ToTokens;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    Arm, Attribute, BinOp, Block, Expr, ExprAssign, ExprAsync, ExprAwait, ExprBinary, ExprCall,
+    ExprClosure, ExprField, ExprForLoop, ExprIf, ExprLoop, ExprMethodCall, ExprParen, ExprPath,
+    ExprReference, ExprStruct, ExprTry, ExprUnsafe, ExprWhile, Fields, FnArg, GenericArgument,
+    GenericParam, Generics, ImplItem, Item, ItemMacro, Local, Macro, Member, Meta, Pat, PatIdent,
+    PatStruct, PatTupleStruct, PatType, PathArguments, ReturnType, Signature, StaticMutability,
+    Stmt, TraitBoundModifier, TraitItem, TypeParamBound, WherePredicate,
+};
+
+use crate::{
+    crate_tree::SourceFile,
+    imports::Import,
+    symbols::{visibility_text, ItemKind, Resolution, SymbolTable, TypeKind, ValueKind},
+};
 
 /// A Rust codebase indexer for Neo4j.
 /// Analyzes a Rust project and stores its structure and relationships in a graph database.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
-    /// Path to the Rust project directory to index.
+    /// Path to the Rust project directory to index, such as a package or
+    /// workspace root.
     #[arg(short, long)]
     path: PathBuf,
 
@@ -27,19 +52,112 @@ struct Cli {
     password: String,
 }
 
+/// Identifies the node of an indexed item, so that edges can be attached to
+/// it after it has been created.
+enum ItemRef<'a> {
+    /// An item identified by its label and `symbol_id`, e.g., a `:Function`
+    /// or `:Struct`.
+    Symbol { label: &'static str, id: &'a str },
+    /// A method, with the `symbol_id` of the type or trait it belongs to.
+    Method { id: &'a str, owner: &'a str },
+    /// A module, identified by its full path.
+    Module { path: &'a str },
+}
+
+impl ItemRef<'_> {
+    /// Cypher clause binding the item's node to `var`.
+    fn match_clause(&self, var: &str) -> String {
+        match self {
+            ItemRef::Symbol { label, .. } => format!(
+                "MATCH ({}:{} {{symbol_id: $item_id, project: $project}})",
+                var, label
+            ),
+            ItemRef::Method { .. } => format!(
+                "MATCH ({}:Method {{symbol_id: $item_id, project: $project}})",
+                var
+            ),
+            // Module nodes are keyed on their full path, so they can be
+            // merged before the file defining them has been processed.
+            ItemRef::Module { .. } => format!(
+                "MERGE ({}:Module {{path: $item_id, project: $project}})",
+                var
+            ),
+        }
+    }
+
+    /// Adds the parameters used by [`ItemRef::match_clause`] to `query`.
+    fn bind(&self, query: Query) -> Query {
+        query.param("item_id", self.id())
+    }
+
+    /// The `symbol_id` of the item, or the path of a module, which nodes
+    /// owned by the item are scoped to, e.g., `my_crate::User::new`.
+    fn id(&self) -> &str {
+        match self {
+            ItemRef::Symbol { id, .. } | ItemRef::Method { id, .. } => id,
+            ItemRef::Module { path } => path,
+        }
+    }
+}
+
+/// The type or enum variant that owns a set of fields.
+struct FieldOwner<'a> {
+    /// The owner's node: a `:Struct`, `:Union`, or `:Variant`.
+    node: ItemRef<'a>,
+    /// Whether the owner is effectively public.
+    public: bool,
+    /// The visibility that fields of enum variants take from their enum,
+    /// since they have none of their own.
+    inherited: Option<&'a str>,
+}
+
 /// Represents the different kinds of interactions we can find in the code.
+///
+/// Targets in the project are identified by their `symbol_id`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum Interaction {
-    /// A call to a function, e.g., `my_function()`.
+    /// A call to a function, e.g., `my_function()` or `utils::helper()`.
     FunctionCall(String),
-    /// An instantiation of a struct, e.g., `User { ... }`.
+    /// A call to an associated function or method through its type or
+    /// trait, e.g., `User::new()` or `<T as Shape>::area(&t)`.
+    AssociatedCall(String),
+    /// A construction of an enum variant, e.g., `Status::Active`,
+    /// `Shape::Circle(1.0)`, or `Event::Click { x, y }`.
+    VariantConstruction(String),
+    /// A pattern matching an enum variant, e.g., `Status::Inactive { .. }`
+    /// in a `match` arm, `if let`, or `let else`.
+    VariantMatch(String),
+    /// A read of a field of a project type, e.g., `self.name`.
+    FieldRead(String),
+    /// An assignment to, or mutable borrow of, a field of a project type,
+    /// e.g., `self.count += 1`.
+    FieldWrite(String),
+    /// An instantiation of a struct or union, e.g., `User { ... }`.
     StructInstantiation(String),
+    /// A use of a project const or static, e.g., `MAX_USERS`.
+    ValueRead(String),
+    /// An assignment to, or mutable borrow of, a project static, e.g.,
+    /// `COUNTER += 1`.
+    ValueWrite(String),
+    /// An invocation of a macro, e.g., `log!(...)`. Macros from outside the
+    /// project are identified by their name.
+    MacroInvocation(String),
+    /// A method call, e.g., `user.save()`, with the receiver's type when it
+    /// is known to be a project type or trait.
+    MethodCall {
+        name: String,
+        receiver: Option<String>,
+    },
+    /// A call to a bare name that is not in scope, as written, e.g.,
+    /// `helper()` without a matching definition or import.
+    UnresolvedCall(String),
 }
 
 /// Main entry point for the application.
 ///
 /// This function parses command-line arguments, connects to the Neo4j database,
-/// and iterates through all `.rs` files in the target project directory to
-/// begin the indexing process.
+/// and indexes every file reachable from the crate roots found in the target
+/// project directory.
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
     // Load .env file for fallback configuration
@@ -62,34 +180,58 @@ async fn main() -> Result<()> {
         .run(query("MERGE (p:Project {name: $name})").param("name", &*project_name))
         .await?;
 
-    for entry in WalkDir::new(&args.path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-    {
-        let path = entry.path();
-        let file_path = path.to_string_lossy().to_string();
-        println!("Processing: {}", file_path);
+    // Load every crate up front so the symbol table sees the whole project.
+    let mut sources = Vec::new();
+    for root in crate_tree::find_crate_roots(&args.path) {
+        println!("Crate: {} ({})", root.name, root.path.display());
+        graph
+            .run(
+                query(
+                    "
+                    MATCH (p:Project {name: $project})
+                    MERGE (c:Crate {symbol_id: $id, project: $project})
+                    SET c.name = $name, c.package = $package, c.kind = $kind
+                    SET c.version = $version, c.edition = $edition
+                    SET c.root = $root
+                    MERGE (p)-[:HAS_CRATE]->(c)
+                ",
+                )
+                .param("project", &*project_name)
+                .param("id", &*root.name)
+                .param("name", &*root.target)
+                .param("package", &*root.package.name)
+                .param("kind", root.kind.as_str())
+                .param("version", root.package.version.clone())
+                .param("edition", root.package.edition.clone())
+                .param("root", root.path.to_string_lossy().to_string()),
+            )
+            .await?;
+        sources.extend(crate_tree::load_crate(&root));
+    }
+    let symbols = SymbolTable::build(&sources);
 
-        let code = fs::read_to_string(path)?;
+    for source in sources {
+        let file_path = source.path.to_string_lossy().to_string();
+        println!("Processing: {}", file_path);
 
         graph
             .run(
                 query(
                     "
                     MATCH (p:Project {name: $project})
+                    MATCH (c:Crate {symbol_id: $crate, project: $project})
                     MERGE (f:File {path: $path})
                     MERGE (p)-[:CONTAINS_FILE]->(f)
+                    MERGE (c)-[:CONTAINS_FILE]->(f)
                 ",
                 )
                 .param("project", &*project_name)
+                .param("crate", &*source.crate_name)
                 .param("path", &*file_path),
             )
             .await?;
 
-        if let Ok(ast) = syn::parse_file(&code) {
-            process_ast(&graph, &project_name, &file_path, ast).await?;
-        }
+        process_ast(&graph, &project_name, &file_path, &symbols, source).await?;
     }
 
     println!("✅ Indexing complete for project: {}!", project_name);
@@ -98,196 +240,3091 @@ async fn main() -> Result<()> {
 
 /// Processes the Abstract Syntax Tree (AST) of a single Rust file.
 ///
-/// This function iterates through the top-level items of a file's AST
-/// (like functions, structs, and traits) and creates the corresponding nodes
-/// and relationships in the Neo4j database.
-async fn process_ast(graph: &Graph, project: &str, file_path: &str, ast: syn::File) -> Result<()> {
-    for item in ast.items {
-        match item {
-            Item::Fn(item_fn) => {
-                let func_name = item_fn.sig.ident.to_string();
-                // Create the :Function node and link it to its file.
+/// This function creates the `:Module` defined by the file, then walks the
+/// items of its AST (like functions, structs, and traits), descending into
+/// inline `mod { ... }` blocks, and creates the corresponding nodes and
+/// relationships in the Neo4j database. Every item is attached both to its
+/// file and to its enclosing `:Module`.
+async fn process_ast(
+    graph: &Graph,
+    project: &str,
+    file_path: &str,
+    symbols: &SymbolTable,
+    source: SourceFile,
+) -> Result<()> {
+    graph
+        .run(
+            query(
+                "
+                MATCH (f:File {path: $path})
+                MERGE (m:Module {path: $module, project: $project})
+                SET m.symbol_id = $module, m.name = $name
+                SET m.visibility = $visibility, m.effectively_public = $effectively_public
+                MERGE (f)-[:DEFINES_MODULE]->(m)
+            ",
+            )
+            .param("path", file_path)
+            .param("module", &*source.module_path)
+            .param(
+                "name",
+                source.module_path.rsplit("::").next().unwrap_or_default(),
+            )
+            .param("visibility", symbols.visibility(&source.module_path))
+            .param(
+                "effectively_public",
+                symbols.is_effectively_public(&source.module_path),
+            )
+            .param("project", project),
+        )
+        .await?;
+    if source.parent_module.is_none() {
+        graph
+            .run(
+                query(
+                    "
+                    MATCH (c:Crate {symbol_id: $crate, project: $project})
+                    MATCH (m:Module {path: $module, project: $project})
+                    MERGE (c)-[:ROOT_MODULE]->(m)
+                ",
+                )
+                .param("crate", &*source.crate_name)
+                .param("module", &*source.module_path)
+                .param("project", project),
+            )
+            .await?;
+    }
+    if let Some(parent) = &source.parent_module {
+        graph
+            .run(
+                query(
+                    "
+                    MATCH (parent:Module {path: $parent, project: $project})
+                    MATCH (m:Module {path: $module, project: $project})
+                    MERGE (parent)-[:CONTAINS]->(m)
+                ",
+                )
+                .param("parent", &**parent)
+                .param("module", &*source.module_path)
+                .param("project", project),
+            )
+            .await?;
+    }
+
+    // Inner attributes such as `#![cfg(test)]` apply to the file's module.
+    let module = ItemRef::Module {
+        path: &source.module_path,
+    };
+    record_attributes(
+        graph,
+        project,
+        symbols,
+        &source.module_path,
+        &module,
+        &source.ast.attrs,
+    )
+    .await?;
+
+    // Inline modules are queued instead of recursed into, so nesting depth
+    // does not require boxing the async call.
+    let mut pending = vec![(source.module_path, source.ast.items)];
+    while let Some((module_path, items)) = pending.pop() {
+        for item in items {
+            process_item(
+                graph,
+                project,
+                file_path,
+                symbols,
+                &module_path,
+                item,
+                &mut pending,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Processes a single item belonging to the module at `module_path`.
+///
+/// Inline `mod` blocks get their own `:Module` node nested under the current
+/// one, and their items are pushed onto `pending` to be processed afterwards.
+async fn process_item(
+    graph: &Graph,
+    project: &str,
+    file_path: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    item: Item,
+    pending: &mut Vec<(String, Vec<Item>)>,
+) -> Result<()> {
+    match item {
+        Item::Mod(item_mod) => {
+            let child_path = format!("{}::{}", module_path, item_mod.ident);
+            // Attributes on `mod foo;` apply to the module in `foo.rs`.
+            let module = ItemRef::Module { path: &child_path };
+            record_attributes(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &module,
+                &item_mod.attrs,
+            )
+            .await?;
+            record_location(graph, project, file_path, &module, &item_mod).await?;
+            // Out-of-line `mod foo;` declarations have no content here.
+            if let Some((_, items)) = item_mod.content {
                 graph
                     .run(
                         query(
                             "
                             MATCH (f:File {path: $path})
-                            MERGE (fn:Function {name: $name, project: $project})
-                            MERGE (f)-[:CONTAINS]->(fn)
+                            MATCH (parent:Module {path: $parent, project: $project})
+                            MERGE (m:Module {path: $module, project: $project})
+                            SET m.symbol_id = $module, m.name = $name
+                            SET m.visibility = $visibility, m.effectively_public = $effectively_public
+                            MERGE (parent)-[:CONTAINS]->(m)
+                            MERGE (f)-[:CONTAINS]->(m)
                         ",
                         )
                         .param("path", file_path)
-                        .param("name", &*func_name)
+                        .param("parent", module_path)
+                        .param("module", &*child_path)
+                        .param("name", item_mod.ident.to_string())
+                        .param("visibility", symbols.visibility(&child_path))
+                        .param("effectively_public", symbols.is_effectively_public(&child_path))
                         .param("project", project),
                     )
                     .await?;
+                pending.push((child_path, items));
+            }
+        }
+        Item::Fn(item_fn) => {
+            let item_path = format!("{}::{}", module_path, item_fn.sig.ident);
+            let item = ItemNode {
+                label: "Function",
+                id: &item_path,
+                name: item_fn.sig.ident.to_string(),
+                properties: Vec::new(),
+                attrs: &item_fn.attrs,
+                syntax: &item_fn,
+            };
+            let caller =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_signature(graph, project, symbols, module_path, &caller, &item_fn.sig).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &caller,
+                &item_fn.sig.generics,
+            )
+            .await?;
+            record_interactions(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &caller,
+                &item_fn.sig,
+                &item_fn.block,
+            )
+            .await?;
+        }
+        Item::Struct(item_struct) => {
+            let item_path = format!("{}::{}", module_path, item_struct.ident);
+            let item = ItemNode {
+                label: "Struct",
+                id: &item_path,
+                name: item_struct.ident.to_string(),
+                properties: Vec::new(),
+                attrs: &item_struct.attrs,
+                syntax: &item_struct,
+            };
+            let item =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &item,
+                &item_struct.generics,
+            )
+            .await?;
+            let owner = FieldOwner {
+                node: item,
+                public: symbols.is_effectively_public(&item_path),
+                inherited: None,
+            };
+            record_fields(
+                graph,
+                project,
+                file_path,
+                symbols,
+                module_path,
+                &owner,
+                &item_struct.fields,
+            )
+            .await?;
+        }
+        Item::Trait(item_trait) => {
+            let item_path = format!("{}::{}", module_path, item_trait.ident);
+            let trait_name = item_trait.ident.to_string();
+            let item = ItemNode {
+                label: "Trait",
+                id: &item_path,
+                name: trait_name.clone(),
+                properties: Vec::new(),
+                attrs: &item_trait.attrs,
+                syntax: &item_trait,
+            };
+            let item =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &item,
+                &item_trait.generics,
+            )
+            .await?;
+            // Supertraits are bounds on `Self`.
+            for supertrait in bound_traits(&item_trait.supertraits) {
+                let supertrait_id = symbols.trait_id(module_path, &supertrait);
+                graph
+                    .run(
+                        query(
+                            "
+                            MATCH (t:Trait {symbol_id: $id, project: $project})
+                            MERGE (st:Trait {symbol_id: $supertrait, project: $project})
+                            ON CREATE SET st.name = $supertrait_name
+                            MERGE (t)-[:BOUNDED_BY]->(st)
+                        ",
+                        )
+                        .param("id", &*item_path)
+                        .param("supertrait", &*supertrait_id)
+                        .param("supertrait_name", supertrait.last().map(String::as_str))
+                        .param("project", project),
+                    )
+                    .await?;
+            }
 
-                // Find all interactions within the function body.
-                let mut interactions = Vec::new();
-                for stmt in &item_fn.block.stmts {
-                    find_interactions_in_stmt(stmt, &mut interactions);
-                }
-
-                // Create relationships for each found interaction.
-                for interaction in interactions {
-                    match interaction {
-                        Interaction::FunctionCall(callee_name) => {
-                            graph
-                                .run(
-                                    query(
-                                        "
-                                        MATCH (caller:Function {name: $caller, project: $project})
-                                        MERGE (callee:Function {name: $callee, project: $project})
-                                        MERGE (caller)-[:CALLS]->(callee)
-                                    ",
-                                    )
-                                    .param("caller", &*func_name)
-                                    .param("callee", &*callee_name)
-                                    .param("project", project),
+            for trait_item in &item_trait.items {
+                match trait_item {
+                    TraitItem::Fn(method) => {
+                        let method_name = method.sig.ident.to_string();
+                        let method_path = format!("{}::{}", item_path, method_name);
+                        graph
+                            .run(
+                                query(
+                                    "
+                                    MATCH (t:Trait {symbol_id: $trait_id, project: $project})
+                                    MERGE (m:Method {symbol_id: $id, project: $project})
+                                    SET m.name = $name, m.owner = $trait
+                                    SET m.self_kind = $self_kind, m.provided = $provided
+                                    SET m.visibility = $visibility, m.effectively_public = $effectively_public
+                                    MERGE (t)-[:DECLARES]->(m)
+                                ",
                                 )
-                                .await?;
+                                .param("trait_id", &*item_path)
+                                .param("id", &*method_path)
+                                .param("trait", &*trait_name)
+                                .param("name", &*method_name)
+                                .param("self_kind", self_kind(&method.sig))
+                                .param("provided", method.default.is_some())
+                                .param("visibility", symbols.visibility(&item_path))
+                                .param("effectively_public", symbols.is_effectively_public(&item_path))
+                                .param("project", project),
+                            )
+                            .await?;
+
+                        let caller = ItemRef::Method {
+                            id: &method_path,
+                            owner: &item_path,
+                        };
+                        record_attributes(
+                            graph,
+                            project,
+                            symbols,
+                            module_path,
+                            &caller,
+                            &method.attrs,
+                        )
+                        .await?;
+                        record_location(graph, project, file_path, &caller, method).await?;
+                        record_signature(
+                            graph,
+                            project,
+                            symbols,
+                            module_path,
+                            &caller,
+                            &method.sig,
+                        )
+                        .await?;
+                        record_generics(
+                            graph,
+                            project,
+                            symbols,
+                            module_path,
+                            &caller,
+                            &method.sig.generics,
+                        )
+                        .await?;
+                        // Default bodies call into the rest of the code just
+                        // like any other method.
+                        if let Some(block) = &method.default {
+                            record_interactions(
+                                graph,
+                                project,
+                                symbols,
+                                module_path,
+                                &caller,
+                                &method.sig,
+                                block,
+                            )
+                            .await?;
                         }
-                        Interaction::StructInstantiation(struct_name) => {
-                            graph
-                                .run(
-                                    query(
-                                        "
-                                        MATCH (caller:Function {name: $caller, project: $project})
-                                        MERGE (s:Struct {name: $struct, project: $project})
-                                        MERGE (caller)-[:INSTANTIATES]->(s)
-                                    ",
-                                    )
-                                    .param("caller", &*func_name)
-                                    .param("struct", &*struct_name)
-                                    .param("project", project),
+                    }
+                    TraitItem::Type(assoc_type) => {
+                        graph
+                            .run(
+                                with_location(
+                                    "
+                                    MATCH (t:Trait {symbol_id: $trait_id, project: $project})
+                                    MERGE (a:AssociatedType {symbol_id: $id, project: $project})
+                                    SET a.name = $name, a.owner = $trait, a.provided = $provided
+                                    SET a.visibility = $visibility, a.effectively_public = $effectively_public
+                                    MERGE (t)-[:DECLARES]->(a)
+                                ",
+                                    "a",
+                                    file_path,
+                                    assoc_type,
                                 )
-                                .await?;
-                        }
+                                .param("trait_id", &*item_path)
+                                .param("id", format!("{}::{}", item_path, assoc_type.ident))
+                                .param("trait", &*trait_name)
+                                .param("name", &*assoc_type.ident.to_string())
+                                .param("provided", assoc_type.default.is_some())
+                                .param("visibility", symbols.visibility(&item_path))
+                                .param("effectively_public", symbols.is_effectively_public(&item_path))
+                                .param("project", project),
+                            )
+                            .await?;
+                    }
+                    TraitItem::Const(assoc_const) => {
+                        graph
+                            .run(
+                                with_location(
+                                    "
+                                    MATCH (t:Trait {symbol_id: $trait_id, project: $project})
+                                    MERGE (c:AssociatedConst {symbol_id: $id, project: $project})
+                                    SET c.name = $name, c.owner = $trait, c.type_text = $type_text,
+                                        c.provided = $provided
+                                    SET c.visibility = $visibility, c.effectively_public = $effectively_public
+                                    MERGE (t)-[:DECLARES]->(c)
+                                ",
+                                    "c",
+                                    file_path,
+                                    assoc_const,
+                                )
+                                .param("trait_id", &*item_path)
+                                .param("id", format!("{}::{}", item_path, assoc_const.ident))
+                                .param("trait", &*trait_name)
+                                .param("name", &*assoc_const.ident.to_string())
+                                .param("type_text", &*type_text_of(&assoc_const.ty))
+                                .param("provided", assoc_const.default.is_some())
+                                .param("visibility", symbols.visibility(&item_path))
+                                .param("effectively_public", symbols.is_effectively_public(&item_path))
+                                .param("project", project),
+                            )
+                            .await?;
                     }
+                    _ => {}
                 }
             }
-            Item::Struct(item_struct) => {
-                let struct_name = item_struct.ident.to_string();
+        }
+        Item::Enum(item_enum) => {
+            let item_path = format!("{}::{}", module_path, item_enum.ident);
+            let enum_name = item_enum.ident.to_string();
+            let item = ItemNode {
+                label: "Enum",
+                id: &item_path,
+                name: enum_name.clone(),
+                properties: Vec::new(),
+                attrs: &item_enum.attrs,
+                syntax: &item_enum,
+            };
+            let item =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &item,
+                &item_enum.generics,
+            )
+            .await?;
+
+            for variant in &item_enum.variants {
+                let variant_name = variant.ident.to_string();
+                let variant_path = format!("{}::{}", item_path, variant_name);
+                let kind = match variant.fields {
+                    Fields::Unit => "unit",
+                    Fields::Unnamed(_) => "tuple",
+                    Fields::Named(_) => "struct",
+                };
                 graph
                     .run(
-                        query(
+                        with_location(
                             "
-                            MATCH (f:File {path: $path})
-                            MERGE (s:Struct {name: $name, project: $project})
-                            MERGE (f)-[:CONTAINS]->(s)
+                            MATCH (e:Enum {symbol_id: $enum_id, project: $project})
+                            MERGE (v:Variant {symbol_id: $id, project: $project})
+                            SET v.name = $name, v.enum = $enum, v.kind = $kind,
+                                v.visibility = $visibility,
+                                v.effectively_public = $effectively_public
+                            MERGE (e)-[:HAS_VARIANT]->(v)
                         ",
+                            "v",
+                            file_path,
+                            variant,
+                        )
+                        .param("enum_id", &*item_path)
+                        .param("id", &*variant_path)
+                        .param("enum", &*enum_name)
+                        .param("name", &*variant_name)
+                        .param("kind", kind)
+                        .param("visibility", symbols.visibility(&item_path))
+                        .param(
+                            "effectively_public",
+                            symbols.is_effectively_public(&item_path),
                         )
-                        .param("path", file_path)
-                        .param("name", &*struct_name)
                         .param("project", project),
                     )
                     .await?;
+
+                let variant_node = ItemRef::Symbol {
+                    label: "Variant",
+                    id: &variant_path,
+                };
+                record_attributes(
+                    graph,
+                    project,
+                    symbols,
+                    module_path,
+                    &variant_node,
+                    &variant.attrs,
+                )
+                .await?;
+
+                // Fields of a variant are as visible as the enum itself.
+                let field_owner = FieldOwner {
+                    node: variant_node,
+                    public: symbols.is_effectively_public(&item_path),
+                    inherited: Some(symbols.visibility(&item_path)),
+                };
+                record_fields(
+                    graph,
+                    project,
+                    file_path,
+                    symbols,
+                    module_path,
+                    &field_owner,
+                    &variant.fields,
+                )
+                .await?;
             }
-            Item::Trait(item_trait) => {
-                let trait_name = item_trait.ident.to_string();
-                 graph
+        }
+        Item::Union(item_union) => {
+            let item_path = format!("{}::{}", module_path, item_union.ident);
+            let item = ItemNode {
+                label: "Union",
+                id: &item_path,
+                name: item_union.ident.to_string(),
+                properties: Vec::new(),
+                attrs: &item_union.attrs,
+                syntax: &item_union,
+            };
+            let item =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &item,
+                &item_union.generics,
+            )
+            .await?;
+
+            let fields = Fields::Named(item_union.fields.clone());
+            let owner = FieldOwner {
+                node: item,
+                public: symbols.is_effectively_public(&item_path),
+                inherited: None,
+            };
+            record_fields(
+                graph,
+                project,
+                file_path,
+                symbols,
+                module_path,
+                &owner,
+                &fields,
+            )
+            .await?;
+        }
+        Item::Type(item_type) => {
+            let item_path = format!("{}::{}", module_path, item_type.ident);
+            let item = ItemNode {
+                label: "TypeAlias",
+                id: &item_path,
+                name: item_type.ident.to_string(),
+                properties: vec![("type_text", type_text_of(&item_type.ty).into())],
+                attrs: &item_type.attrs,
+                syntax: &item_type,
+            };
+            let item =
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            record_generics(
+                graph,
+                project,
+                symbols,
+                module_path,
+                &item,
+                &item_type.generics,
+            )
+            .await?;
+
+            if let Some((type_path, kind)) = project_type(&item_type.ty, symbols, module_path) {
+                graph
                     .run(
-                        query(
+                        query(&format!(
                             "
-                            MATCH (f:File {path: $path})
-                            MERGE (t:Trait {name: $name, project: $project})
-                            MERGE (f)-[:CONTAINS]->(t)
+                            MATCH (a:TypeAlias {{symbol_id: $id, project: $project}})
+                            MERGE (t:{} {{symbol_id: $type, project: $project}})
+                            MERGE (a)-[:ALIASES]->(t)
                         ",
-                        )
-                        .param("path", file_path)
-                        .param("name", &*trait_name)
+                            kind.label()
+                        ))
+                        .param("id", &*item_path)
+                        .param("type", &*type_path)
                         .param("project", project),
                     )
                     .await?;
             }
-            Item::Impl(item_impl) => {
-                // Find `impl Trait for Struct` blocks.
-                if let Some(trait_path) = item_impl.trait_.as_ref().map(|t| &t.1) {
-                    let struct_type = &*item_impl.self_ty;
-                    if let (Some(trait_ident), Some(struct_ident)) = (trait_path.segments.last(), get_ident_from_type(struct_type)) {
-                         graph
-                            .run(
-                                query(
-                                    "
-                                    MERGE (s:Struct {name: $struct, project: $project})
-                                    MERGE (t:Trait {name: $trait, project: $project})
-                                    MERGE (s)-[:IMPLEMENTS]->(t)
-                                ",
-                                )
-                                .param("struct", &*struct_ident)
-                                .param("trait", &*trait_ident.ident.to_string())
-                                .param("project", project),
+        }
+        Item::Const(item_const) => {
+            let item_path = format!("{}::{}", module_path, item_const.ident);
+            let item = ItemNode {
+                label: "Const",
+                id: &item_path,
+                name: item_const.ident.to_string(),
+                properties: vec![("type_text", type_text_of(&item_const.ty).into())],
+                attrs: &item_const.attrs,
+                syntax: &item_const,
+            };
+            create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+        }
+        Item::Static(item_static) => {
+            let item_path = format!("{}::{}", module_path, item_static.ident);
+            let mutable = matches!(item_static.mutability, StaticMutability::Mut(_));
+            let item = ItemNode {
+                label: "Static",
+                id: &item_path,
+                name: item_static.ident.to_string(),
+                properties: vec![
+                    ("type_text", type_text_of(&item_static.ty).into()),
+                    ("mutable", mutable.into()),
+                ],
+                attrs: &item_static.attrs,
+                syntax: &item_static,
+            };
+            create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+        }
+        Item::Macro(item_macro) => {
+            // Only `macro_rules! name { ... }` definitions carry an ident;
+            // other item-position macros are invocations.
+            let definition = item_macro
+                .ident
+                .as_ref()
+                .filter(|_| item_macro.mac.path.is_ident("macro_rules"));
+            if let Some(ident) = definition {
+                let exported = symbols::is_exported(&item_macro);
+                let item_path = if exported {
+                    symbols::macro_path(module_path, ident)
+                } else {
+                    format!("{}::{}", module_path, ident)
+                };
+                let item = ItemNode {
+                    label: "Macro",
+                    id: &item_path,
+                    name: ident.to_string(),
+                    properties: vec![("exported", exported.into())],
+                    attrs: &item_macro.attrs,
+                    syntax: &item_macro,
+                };
+                create_item_node(graph, project, file_path, symbols, module_path, item).await?;
+            }
+        }
+        Item::Use(item_use) => {
+            for import in imports::flatten_use_tree(&item_use.tree) {
+                // `use ::name` always refers to an external crate.
+                let resolution = if item_use.leading_colon.is_some() {
+                    Resolution::External(import.segments.join("::"))
+                } else {
+                    symbols.resolve(module_path, &import.segments)
+                };
+                // Targets are merged rather than matched, since the file that
+                // defines them may not have been processed yet.
+                let (target, key) = match resolution {
+                    Resolution::Item {
+                        path,
+                        kind: ItemKind::Module,
+                    } => (
+                        "MERGE (t:Module {path: $target, project: $project})".to_string(),
+                        path,
+                    ),
+                    Resolution::Item { path, kind } => (
+                        format!(
+                            "MERGE (t:{} {{symbol_id: $target, project: $project}})",
+                            kind.label()
+                        ),
+                        path,
+                    ),
+                    Resolution::Variant { enum_path, name } => (
+                        "MERGE (t:Variant {symbol_id: $target, project: $project})".to_string(),
+                        format!("{}::{}", enum_path, name),
+                    ),
+                    Resolution::External(path) => (
+                        "MERGE (t:ExternalPath {path: $target, project: $project})".to_string(),
+                        path,
+                    ),
+                };
+                graph
+                    .run(
+                        query(&format!(
+                            "
+                            MATCH (m:Module {{path: $module, project: $project}})
+                            {}
+                            MERGE (m)-[r:IMPORTS]->(t)
+                            SET r.alias = $alias, r.glob = $glob
+                        ",
+                            target
+                        ))
+                        .param("module", module_path)
+                        .param("target", key)
+                        .param("alias", import.alias)
+                        .param("glob", import.glob)
+                        .param("project", project),
+                    )
+                    .await?;
+            }
+        }
+        Item::Impl(item_impl) => {
+            // Only impls for our own types are indexed; the owner of the
+            // methods must be a node in the graph.
+            let syn::Type::Path(self_type) = &*item_impl.self_ty else {
+                return Ok(());
+            };
+            let self_segments = symbols::path_segments(&self_type.path);
+            let Some((type_path, kind)) = symbols.type_path(module_path, &self_segments) else {
+                return Ok(());
+            };
+            let type_name = self_segments.last().cloned().unwrap_or_default();
+            let trait_segments = item_impl
+                .trait_
+                .as_ref()
+                .map(|(_, path, _)| symbols::path_segments(path));
+            let trait_id = trait_segments
+                .as_ref()
+                .map(|segments| symbols.trait_id(module_path, segments));
+            let type_public = symbols.is_effectively_public(&type_path);
+
+            // Find `impl Trait for Type` blocks.
+            if let (Some(trait_id), Some(trait_segments)) = (&trait_id, &trait_segments) {
+                graph
+                    .run(
+                        query(&format!(
+                            "
+                            MERGE (s:{} {{symbol_id: $type, project: $project}})
+                            MERGE (t:Trait {{symbol_id: $trait, project: $project}})
+                            ON CREATE SET t.name = $trait_name
+                            MERGE (s)-[:IMPLEMENTS]->(t)
+                        ",
+                            kind.label()
+                        ))
+                        .param("type", &*type_path)
+                        .param("trait", &**trait_id)
+                        .param("trait_name", trait_segments.last().map(String::as_str))
+                        .param("project", project),
+                    )
+                    .await?;
+            }
+
+            for impl_item in &item_impl.items {
+                let ImplItem::Fn(method) = impl_item else {
+                    continue;
+                };
+                let method_name = method.sig.ident.to_string();
+                let method_path = symbols::method_id(&type_path, trait_id.as_deref(), &method_name);
+                // Methods of trait impls are as visible as the trait; inherent
+                // methods need their own `pub`.
+                let visibility = match &trait_id {
+                    Some(_) => "pub".to_string(),
+                    None => visibility_text(&method.vis),
+                };
+                let effectively_public = visibility == "pub" && type_public;
+                graph
+                    .run(
+                        query(&format!(
+                            "
+                            MERGE (s:{} {{symbol_id: $type, project: $project}})
+                            MERGE (m:Method {{symbol_id: $id, project: $project}})
+                            SET m.name = $name, m.owner = $owner, m.self_kind = $self_kind,
+                                m.visibility = $visibility,
+                                m.effectively_public = $effectively_public
+                            MERGE (s)-[:HAS_METHOD]->(m)
+                        ",
+                            kind.label()
+                        ))
+                        .param("type", &*type_path)
+                        .param("id", &*method_path)
+                        .param("owner", &*type_name)
+                        .param("name", &*method_name)
+                        .param("self_kind", self_kind(&method.sig))
+                        .param("visibility", &*visibility)
+                        .param("effectively_public", effectively_public)
+                        .param("project", project),
+                    )
+                    .await?;
+
+                // Trait methods are owned by the trait, whether or not the
+                // trait itself is defined in this project.
+                if let (Some(trait_id), Some(trait_segments)) = (&trait_id, &trait_segments) {
+                    graph
+                        .run(
+                            query(
+                                "
+                                MATCH (m:Method {symbol_id: $id, project: $project})
+                                MERGE (tm:Method {symbol_id: $trait_method, project: $project})
+                                ON CREATE SET tm.name = $name, tm.owner = $trait_name
+                                MERGE (m)-[:IMPLEMENTS_METHOD]->(tm)
+                            ",
                             )
-                            .await?;
-                    }
+                            .param("id", &*method_path)
+                            .param("trait_method", format!("{}::{}", trait_id, method_name))
+                            .param("name", &*method_name)
+                            .param("trait_name", trait_segments.last().map(String::as_str))
+                            .param("project", project),
+                        )
+                        .await?;
                 }
+
+                let caller = ItemRef::Method {
+                    id: &method_path,
+                    owner: &type_path,
+                };
+                record_attributes(graph, project, symbols, module_path, &caller, &method.attrs)
+                    .await?;
+                record_location(graph, project, file_path, &caller, method).await?;
+                record_signature(graph, project, symbols, module_path, &caller, &method.sig)
+                    .await?;
+                // Parameters of the `impl` itself are in scope for each method.
+                record_generics(
+                    graph,
+                    project,
+                    symbols,
+                    module_path,
+                    &caller,
+                    &item_impl.generics,
+                )
+                .await?;
+                record_generics(
+                    graph,
+                    project,
+                    symbols,
+                    module_path,
+                    &caller,
+                    &method.sig.generics,
+                )
+                .await?;
+                record_interactions(
+                    graph,
+                    project,
+                    symbols,
+                    module_path,
+                    &caller,
+                    &method.sig,
+                    &method.block,
+                )
+                .await?;
             }
-            _ => {}
         }
+        _ => {}
     }
     Ok(())
 }
 
-/// Helper function to extract the identifier from a `syn::Type`.
-/// This is used to get the name of a struct from an `impl` block.
-fn get_ident_from_type(ty: &syn::Type) -> Option<String> {
-    if let syn::Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            return Some(segment.ident.to_string());
+/// A module-level item, such as a function, struct, or static, whose node
+/// is created by [`create_item_node`].
+struct ItemNode<'a, S> {
+    label: &'static str,
+    /// The item's `symbol_id`.
+    id: &'a str,
+    name: String,
+    /// Properties only this kind of item has, e.g. a static's `mutable`.
+    properties: Vec<(&'static str, BoltType)>,
+    attrs: &'a [Attribute],
+    /// The item's syntax, whose span is its source location.
+    syntax: &'a S,
+}
+
+/// Creates the node of a module-level item, with its name and visibility,
+/// links it to the file and module containing it, and records its
+/// attributes and location. Returns the node, for attaching further edges.
+async fn create_item_node<'a, S: Spanned>(
+    graph: &Graph,
+    project: &str,
+    file_path: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    item: ItemNode<'a, S>,
+) -> Result<ItemRef<'a>> {
+    let properties: String = item
+        .properties
+        .iter()
+        .map(|(key, _)| format!("SET n.{key} = ${key}\n", key = key))
+        .collect();
+    let mut cypher = query(&format!(
+        "
+        MATCH (f:File {{path: $path}})
+        MATCH (m:Module {{path: $module, project: $project}})
+        MERGE (n:{} {{symbol_id: $id, project: $project}})
+        SET n.name = $name, n.visibility = $visibility, n.effectively_public = $effectively_public
+        {}
+        MERGE (f)-[:CONTAINS]->(n)
+        MERGE (m)-[:CONTAINS]->(n)
+    ",
+        item.label, properties
+    ))
+    .param("path", file_path)
+    .param("module", module_path)
+    .param("id", item.id)
+    .param("name", item.name)
+    .param("visibility", symbols.visibility(item.id))
+    .param("effectively_public", symbols.is_effectively_public(item.id))
+    .param("project", project);
+    for (key, value) in item.properties {
+        cypher = cypher.param(key, value);
+    }
+    graph.run(cypher).await?;
+
+    let node = ItemRef::Symbol {
+        label: item.label,
+        id: item.id,
+    };
+    record_attributes(graph, project, symbols, module_path, &node, item.attrs).await?;
+    record_location(graph, project, file_path, &node, item.syntax).await?;
+    Ok(node)
+}
+
+/// Creates a `:Field` node for each of `fields`, identified by its owner's
+/// `symbol_id` and the field's name, with an `OF_TYPE` edge when the field's
+/// type is one of the project's types.
+async fn record_fields(
+    graph: &Graph,
+    project: &str,
+    file_path: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    owner: &FieldOwner<'_>,
+    fields: &Fields,
+) -> Result<()> {
+    for (index, field) in fields.iter().enumerate() {
+        // Tuple fields are named by their position, like `self.0`.
+        let field_name = field
+            .ident
+            .as_ref()
+            .map_or_else(|| index.to_string(), |ident| ident.to_string());
+        let field_path = format!("{}::{}", owner.node.id(), field_name);
+        let visibility = owner
+            .inherited
+            .map_or_else(|| visibility_text(&field.vis), str::to_string);
+        let effectively_public = owner.public && visibility == "pub";
+        let cypher = format!(
+            "
+            {}
+            MERGE (fd:Field {{symbol_id: $id, project: $project}})
+            SET fd.name = $name, fd.owner = $item_id, fd.index = $index,
+                fd.visibility = $visibility, fd.type_text = $type_text,
+                fd.effectively_public = $effectively_public
+            MERGE (owner)-[:HAS_FIELD]->(fd)
+        ",
+            owner.node.match_clause("owner")
+        );
+        graph
+            .run(
+                owner.node.bind(
+                    with_location(&cypher, "fd", file_path, field)
+                        .param("id", &*field_path)
+                        .param("index", index as i64)
+                        .param("name", &*field_name)
+                        .param("visibility", &*visibility)
+                        .param("effectively_public", effectively_public)
+                        .param("type_text", &*type_text_of(&field.ty))
+                        .param("project", project),
+                ),
+            )
+            .await?;
+        let field_node = ItemRef::Symbol {
+            label: "Field",
+            id: &field_path,
+        };
+        record_attributes(
+            graph,
+            project,
+            symbols,
+            module_path,
+            &field_node,
+            &field.attrs,
+        )
+        .await?;
+
+        if let Some((type_path, kind)) = project_type(&field.ty, symbols, module_path) {
+            graph
+                .run(
+                    query(&format!(
+                        "
+                        MATCH (fd:Field {{symbol_id: $id, project: $project}})
+                        MERGE (t:{} {{symbol_id: $type, project: $project}})
+                        MERGE (fd)-[:OF_TYPE]->(t)
+                    ",
+                        kind.label()
+                    ))
+                    .param("id", &*field_path)
+                    .param("type", &*type_path)
+                    .param("project", project),
+                )
+                .await?;
         }
     }
-    None
+    Ok(())
 }
 
-/// Synchronously and recursively finds interactions within a statement.
-///
-/// This function acts as a dispatcher, checking for interactions in different
-/// statement types, such as `let` bindings and expressions.
-fn find_interactions_in_stmt(stmt: &Stmt, interactions: &mut Vec<Interaction>) {
-    match stmt {
-        Stmt::Local(local) => {
-            if let Some(init) = &local.init {
-                find_interactions_in_expr(&init.expr, interactions);
-            }
+/// Builds a query from `cypher` that also stores where `node` is written on
+/// the node bound to `var`: its file and 1-based start and end positions.
+fn with_location(cypher: &str, var: &str, file_path: &str, node: &impl Spanned) -> Query {
+    let span = node.span();
+    let (start, end) = (span.start(), span.end());
+    query(&format!(
+        "
+        {}
+        SET {var}.file = $file, {var}.start_line = $start_line, {var}.start_col = $start_col,
+            {var}.end_line = $end_line, {var}.end_col = $end_col
+    ",
+        cypher,
+        var = var
+    ))
+    .param("file", file_path)
+    .param("start_line", start.line as i64)
+    .param("start_col", start.column as i64 + 1)
+    .param("end_line", end.line as i64)
+    .param("end_col", end.column as i64 + 1)
+}
+
+/// Stores the source location of `node` on the item's node.
+async fn record_location(
+    graph: &Graph,
+    project: &str,
+    file_path: &str,
+    item: &ItemRef<'_>,
+    node: &impl Spanned,
+) -> Result<()> {
+    let cypher = with_location(&item.match_clause("i"), "i", file_path, node);
+    graph
+        .run(item.bind(cypher.param("project", project)))
+        .await?;
+    Ok(())
+}
+
+/// Stores a function or method's signature on its node: parameter names and
+/// types, return type, and qualifiers. Project types used by parameters and
+/// the return type are linked with `TAKES_PARAM` and `RETURNS` edges.
+async fn record_signature(
+    graph: &Graph,
+    project: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    caller: &ItemRef<'_>,
+    sig: &Signature,
+) -> Result<()> {
+    // The receiver is described by `self_kind`, so only typed inputs count.
+    let params: Vec<&PatType> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => Some(pat_type),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let param_names: Vec<String> = params.iter().map(|p| type_text_of(&p.pat)).collect();
+    let param_types: Vec<String> = params.iter().map(|p| type_text_of(&p.ty)).collect();
+    let return_type = match &sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(type_text_of(ty)),
+    };
+    let abi = sig.abi.as_ref().map(|abi| {
+        abi.name
+            .as_ref()
+            .map_or_else(|| "C".to_string(), |name| name.value())
+    });
+    graph
+        .run(
+            caller.bind(
+                query(&format!(
+                    "
+                    {}
+                    SET caller.param_names = $param_names,
+                        caller.param_types = $param_types,
+                        caller.return_type = $return_type,
+                        caller.is_async = $is_async,
+                        caller.is_const = $is_const,
+                        caller.is_unsafe = $is_unsafe,
+                        caller.abi = $abi
+                ",
+                    caller.match_clause("caller")
+                ))
+                .param("param_names", param_names.clone())
+                .param("param_types", param_types)
+                .param("return_type", return_type)
+                .param("is_async", sig.asyncness.is_some())
+                .param("is_const", sig.constness.is_some())
+                .param("is_unsafe", sig.unsafety.is_some())
+                .param("abi", abi)
+                .param("project", project),
+            ),
+        )
+        .await?;
+
+    for (index, (param, name)) in params.iter().zip(&param_names).enumerate() {
+        let passing = match &*param.ty {
+            syn::Type::Reference(reference) if reference.mutability.is_some() => "mut_ref",
+            syn::Type::Reference(_) => "ref",
+            _ => "value",
+        };
+        for (type_path, kind) in project_types_in(&param.ty, symbols, module_path) {
+            graph
+                .run(
+                    caller.bind(
+                        query(&format!(
+                            "
+                            {}
+                            MERGE (t:{} {{symbol_id: $type, project: $project}})
+                            MERGE (caller)-[r:TAKES_PARAM {{index: $index}}]->(t)
+                            SET r.name = $name, r.passing = $passing
+                        ",
+                            caller.match_clause("caller"),
+                            kind.label()
+                        ))
+                        .param("type", &*type_path)
+                        .param("index", index as i64)
+                        .param("name", &**name)
+                        .param("passing", passing)
+                        .param("project", project),
+                    ),
+                )
+                .await?;
         }
-        Stmt::Expr(expr, _) => {
-            find_interactions_in_expr(expr, interactions);
+    }
+
+    if let ReturnType::Type(_, ty) = &sig.output {
+        for (type_path, kind) in project_types_in(ty, symbols, module_path) {
+            graph
+                .run(
+                    caller.bind(
+                        query(&format!(
+                            "
+                            {}
+                            MERGE (t:{} {{symbol_id: $type, project: $project}})
+                            MERGE (caller)-[:RETURNS]->(t)
+                        ",
+                            caller.match_clause("caller"),
+                            kind.label()
+                        ))
+                        .param("type", &*type_path)
+                        .param("project", project),
+                    ),
+                )
+                .await?;
         }
-        _ => {}
     }
+    Ok(())
 }
 
-/// Synchronously and recursively finds interactions within an expression.
+/// Records what an item's attributes say about it: `DERIVES` edges for
+/// `#[derive(...)]`, the `cfg` predicates it is compiled under, whether it is
+/// a test, its doc comment, and a `HAS_ATTRIBUTE` edge for every other
+/// attribute.
 ///
-/// This is the core of the analysis, traversing the expression tree to find
-/// function calls, struct instantiations, and other patterns of interest.
-fn find_interactions_in_expr(expr: &Expr, interactions: &mut Vec<Interaction>) {
-    match expr {
-        Expr::Call(ExprCall { func, .. }) => {
-            if let Expr::Path(ExprPath { path, .. }) = &**func {
-                if let Some(ident) = path.get_ident() {
-                    interactions.push(Interaction::FunctionCall(ident.to_string()));
-                }
-            }
-        }
-        Expr::Struct(ExprStruct { path, .. }) => {
-            if let Some(ident) = path.get_ident() {
-                interactions.push(Interaction::StructInstantiation(ident.to_string()));
-            }
-        }
-        Expr::Block(block) => {
-            for stmt in &block.block.stmts {
-                find_interactions_in_stmt(stmt, interactions);
-            }
-        }
-        Expr::If(expr_if) => {
-            for stmt in &expr_if.then_branch.stmts {
-                find_interactions_in_stmt(stmt, interactions);
-            }
-            if let Some((_, else_expr)) = &expr_if.else_branch {
-                find_interactions_in_expr(else_expr, interactions);
+/// Intra-doc links are resolved from `module_path` and become
+/// `DOCUMENTS_REF` edges; links that name nothing in the project are kept in
+/// `unresolved_doc_links`.
+async fn record_attributes(
+    graph: &Graph,
+    project: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    item: &ItemRef<'_>,
+    attrs: &[Attribute],
+) -> Result<()> {
+    let attributes = attributes::classify(attrs);
+    let node = item.match_clause("i");
+
+    let cfg: Vec<String> = attributes
+        .cfgs
+        .iter()
+        .map(|list| type_text_of(&list.tokens))
+        .collect();
+    // Functions and methods always say whether they are tests, so that
+    // `is_test = false` matches ordinary ones.
+    let is_function = matches!(
+        item,
+        ItemRef::Method { .. }
+            | ItemRef::Symbol {
+                label: "Function",
+                ..
             }
+    );
+    let mut properties = Vec::new();
+    if !cfg.is_empty() {
+        properties.push("i.cfg = $cfg");
+    }
+    if is_function || attributes.is_test {
+        properties.push("i.is_test = $is_test");
+    }
+    if !properties.is_empty() {
+        let cypher = query(&format!(
+            "
+            {}
+            SET {}
+        ",
+            node,
+            properties.join(", ")
+        ))
+        .param("cfg", cfg)
+        .param("is_test", attributes.is_test)
+        .param("project", project);
+        graph.run(item.bind(cypher)).await?;
+    }
+
+    if let Some(doc) = &attributes.doc {
+        let mut unresolved = Vec::new();
+        for link in attributes::doc_links(doc) {
+            let segments: Vec<String> = link.split("::").map(str::to_string).collect();
+            let Some((pattern, target)) = doc_link_target(symbols, module_path, &segments) else {
+                unresolved.push(link);
+                continue;
+            };
+            let cypher = query(&format!(
+                "
+                {}
+                MERGE (t:{})
+                MERGE (i)-[:DOCUMENTS_REF]->(t)
+            ",
+                node, pattern
+            ))
+            .param("target", target)
+            .param("project", project);
+            graph.run(item.bind(cypher)).await?;
         }
-        _ => {}
+        let cypher = query(&format!(
+            "
+            {}
+            SET i.doc = $doc, i.unresolved_doc_links = $unresolved
+        ",
+            node
+        ))
+        .param("doc", &**doc)
+        .param("unresolved", unresolved)
+        .param("project", project);
+        graph.run(item.bind(cypher)).await?;
+    }
+
+    for derived in &attributes.derives {
+        let cypher = query(&format!(
+            "
+            {}
+            MERGE (t:Trait {{symbol_id: $trait, project: $project}})
+            ON CREATE SET t.name = $name
+            MERGE (i)-[:DERIVES]->(t)
+        ",
+            node
+        ))
+        .param(
+            "trait",
+            symbols.trait_id(module_path, std::slice::from_ref(derived)),
+        )
+        .param("name", &**derived)
+        .param("project", project);
+        graph.run(item.bind(cypher)).await?;
+    }
+
+    for (path, meta) in &attributes.other {
+        let args = match meta {
+            Meta::Path(_) => None,
+            Meta::List(list) => Some(type_text_of(&list.tokens)),
+            Meta::NameValue(name_value) => Some(type_text_of(&name_value.value)),
+        };
+        let cypher = query(&format!(
+            "
+            {}
+            MERGE (a:Attribute {{path: $path, project: $project}})
+            MERGE (i)-[r:HAS_ATTRIBUTE]->(a)
+            SET r.args = $args
+        ",
+            node
+        ))
+        .param("path", &**path)
+        .param("args", args)
+        .param("project", project);
+        graph.run(item.bind(cypher)).await?;
+    }
+    Ok(())
+}
+
+/// Resolves the path of an intra-doc link to the node pattern of its target
+/// and the `$target` it is identified by, or `None` if it names nothing in
+/// the project.
+fn doc_link_target(
+    symbols: &SymbolTable,
+    module_path: &str,
+    segments: &[String],
+) -> Option<(String, String)> {
+    if let Resolution::Variant { enum_path, name } = symbols.resolve(module_path, segments) {
+        return Some((
+            "Variant {symbol_id: $target, project: $project}".to_string(),
+            format!("{}::{}", enum_path, name),
+        ));
+    }
+    if let Some((path, kind)) = symbols.lookup(module_path, segments, |_| true) {
+        let pattern = match kind {
+            ItemKind::Module => "Module {path: $target, project: $project}".to_string(),
+            kind => format!("{} {{symbol_id: $target, project: $project}}", kind.label()),
+        };
+        return Some((pattern, path));
+    }
+    // `Type::method` names a method of a project type or trait.
+    let (name, owner_path) = segments.split_last()?;
+    let (owner, _) = symbols.lookup(module_path, owner_path, |kind| {
+        matches!(kind, ItemKind::Type(_) | ItemKind::Trait)
+    })?;
+    symbols.method(&owner, name).map(|id| {
+        (
+            "Method {symbol_id: $target, project: $project}".to_string(),
+            id.to_string(),
+        )
+    })
+}
+
+/// Creates a `:TypeParam` node for each generic parameter of an item, scoped
+/// to that item. Lifetimes and const generics are recorded with their `kind`,
+/// and type parameters get `BOUNDED_BY` edges to the traits that constrain
+/// them, whether in the parameter list or in the `where` clause.
+async fn record_generics(
+    graph: &Graph,
+    project: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    item: &ItemRef<'_>,
+    generics: &Generics,
+) -> Result<()> {
+    for (index, param) in generics.params.iter().enumerate() {
+        let mut traits = Vec::new();
+        let mut lifetimes = Vec::new();
+        let (name, kind, type_text_value, default) = match param {
+            GenericParam::Type(type_param) => {
+                traits.extend(bound_traits(&type_param.bounds));
+                lifetimes.extend(bound_lifetimes(&type_param.bounds));
+                (
+                    type_param.ident.to_string(),
+                    "type",
+                    None,
+                    type_param.default.as_ref().map(type_text_of),
+                )
+            }
+            GenericParam::Lifetime(lifetime_param) => {
+                lifetimes.extend(lifetime_param.bounds.iter().map(|l| l.to_string()));
+                (lifetime_param.lifetime.to_string(), "lifetime", None, None)
+            }
+            GenericParam::Const(const_param) => (
+                const_param.ident.to_string(),
+                "const",
+                Some(type_text_of(&const_param.ty)),
+                const_param.default.as_ref().map(type_text_of),
+            ),
+        };
+
+        // `where T: Trait` and `where 'a: 'b` constrain parameters too.
+        for predicate in generics.where_clause.iter().flat_map(|w| &w.predicates) {
+            match predicate {
+                WherePredicate::Type(predicate) if type_text_of(&predicate.bounded_ty) == name => {
+                    traits.extend(bound_traits(&predicate.bounds));
+                    lifetimes.extend(bound_lifetimes(&predicate.bounds));
+                }
+                WherePredicate::Lifetime(predicate) if predicate.lifetime.to_string() == name => {
+                    lifetimes.extend(predicate.bounds.iter().map(|l| l.to_string()));
+                }
+                _ => {}
+            }
+        }
+
+        let param_path = format!("{}::{}", item.id(), name);
+        graph
+            .run(
+                item.bind(
+                    query(&format!(
+                        "
+                        {}
+                        MERGE (tp:TypeParam {{symbol_id: $id, project: $project}})
+                        SET tp.name = $name, tp.owner = $item_id, tp.kind = $kind,
+                            tp.index = $index,
+                            tp.type_text = $type_text,
+                            tp.default = $default,
+                            tp.lifetime_bounds = $lifetime_bounds
+                        MERGE (item)-[:HAS_TYPE_PARAM]->(tp)
+                    ",
+                        item.match_clause("item")
+                    ))
+                    .param("id", &*param_path)
+                    .param("name", &*name)
+                    .param("kind", kind)
+                    .param("index", index as i64)
+                    .param("type_text", type_text_value)
+                    .param("default", default)
+                    .param("lifetime_bounds", lifetimes)
+                    .param("project", project),
+                ),
+            )
+            .await?;
+
+        for bound in traits {
+            graph
+                .run(
+                    query(
+                        "
+                        MATCH (tp:TypeParam {symbol_id: $id, project: $project})
+                        MERGE (t:Trait {symbol_id: $trait, project: $project})
+                        ON CREATE SET t.name = $trait_name
+                        MERGE (tp)-[:BOUNDED_BY]->(t)
+                    ",
+                    )
+                    .param("id", &*param_path)
+                    .param("trait", symbols.trait_id(module_path, &bound))
+                    .param("trait_name", bound.last().map(String::as_str))
+                    .param("project", project),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds the interactions in a function or method body and creates a
+/// relationship from the caller for each of them.
+async fn record_interactions(
+    graph: &Graph,
+    project: &str,
+    symbols: &SymbolTable,
+    module_path: &str,
+    caller: &ItemRef<'_>,
+    sig: &Signature,
+    block: &Block,
+) -> Result<()> {
+    let interactions = find_interactions(symbols, module_path, caller, sig, block);
+
+    // Create relationships for each found interaction.
+    for (interaction, sites) in group_sites(interactions) {
+        let (target, relationship, id) = match &interaction {
+            Interaction::FunctionCall(id) => ("Function", "CALLS", id),
+            Interaction::AssociatedCall(id) => ("Method", "CALLS", id),
+            Interaction::VariantConstruction(id) => ("Variant", "CONSTRUCTS_VARIANT", id),
+            Interaction::VariantMatch(id) => ("Variant", "MATCHES_VARIANT", id),
+            Interaction::FieldRead(id) => ("Field", "READS_FIELD", id),
+            Interaction::FieldWrite(id) => ("Field", "WRITES_FIELD", id),
+            Interaction::MacroInvocation(id) => ("Macro", "INVOKES_MACRO", id),
+            Interaction::StructInstantiation(id) => (
+                symbols.kind(id).map_or("Struct", ItemKind::label),
+                "INSTANTIATES",
+                id,
+            ),
+            Interaction::ValueRead(id) => match symbols.kind(id) {
+                Some(ItemKind::Value(ValueKind::Static)) => ("Static", "READS_STATIC", id),
+                _ => ("Const", "READS_CONST", id),
+            },
+            Interaction::ValueWrite(id) => ("Static", "WRITES_STATIC", id),
+            Interaction::UnresolvedCall(text) => {
+                let cypher = query(&format!(
+                    "
+                    {}
+                    MERGE (t:Unresolved {{text: $text, project: $project}})
+                    MERGE (caller)-[r:CALLS]->(t)
+                    {sites}
+                ",
+                    caller.match_clause("caller"),
+                    sites = SITE_PROPERTIES
+                ))
+                .param("text", &**text);
+                record_sites(graph, project, caller, cypher, &sites).await?;
+                continue;
+            }
+            Interaction::MethodCall { name, receiver } => {
+                let (pattern, key) = method_call_target(symbols, name, receiver.as_deref());
+                let cypher = query(&format!(
+                    "
+                    {}
+                    MERGE (t:{})
+                    MERGE (caller)-[r:CALLS_METHOD {{name: $name}}]->(t)
+                    {sites}
+                ",
+                    caller.match_clause("caller"),
+                    pattern,
+                    sites = SITE_PROPERTIES
+                ))
+                .param("target", key)
+                .param("name", &**name);
+                record_sites(graph, project, caller, cypher, &sites).await?;
+                continue;
+            }
+        };
+        // Targets may not have been processed yet, and macros from outside
+        // the project never are, so they are named when first created.
+        let name = id.rsplit("::").next().unwrap_or(id);
+        let cypher = query(&format!(
+            "
+            {}
+            MERGE (t:{} {{symbol_id: $target, project: $project}})
+            ON CREATE SET t.name = $name
+            MERGE (caller)-[r:{}]->(t)
+            {sites}
+        ",
+            caller.match_clause("caller"),
+            target,
+            relationship,
+            sites = SITE_PROPERTIES
+        ))
+        .param("target", &**id)
+        .param("name", name);
+        record_sites(graph, project, caller, cypher, &sites).await?;
+    }
+    Ok(())
+}
+
+/// Finds the interactions in the body of `caller`, each paired with the site
+/// it occurs at.
+fn find_interactions(
+    symbols: &SymbolTable,
+    module_path: &str,
+    caller: &ItemRef<'_>,
+    sig: &Signature,
+    block: &Block,
+) -> Vec<(Interaction, Site)> {
+    // Methods, including trait default methods, know the type of `self`
+    // from their owner.
+    let self_type = match caller {
+        ItemRef::Method { owner, .. } => Some(*owner),
+        _ => None,
+    };
+    let mut finder = InteractionFinder::new(symbols, module_path, self_type, sig);
+    finder.visit_block(block);
+    finder.interactions
+}
+
+/// The node pattern a method call named `name` points at, and the `$target`
+/// it is identified by.
+///
+/// Methods the project does not define, such as derived or `std` trait
+/// methods, are attributed to the receiver type. Calls on receivers of
+/// unknown type point at an `:Unresolved` node for the method name, written
+/// as `.name`.
+fn method_call_target(
+    symbols: &SymbolTable,
+    name: &str,
+    receiver: Option<&str>,
+) -> (String, String) {
+    let Some(receiver) = receiver else {
+        return (
+            "Unresolved {text: $target, project: $project}".to_string(),
+            format!(".{}", name),
+        );
+    };
+    let (label, id) = match symbols.method(receiver, name) {
+        Some(method) => ("Method", method.to_string()),
+        None => (
+            symbols.kind(receiver).map_or("Trait", ItemKind::label),
+            receiver.to_string(),
+        ),
+    };
+    (
+        format!("{} {{symbol_id: $target, project: $project}}", label),
+        id,
+    )
+}
+
+/// Groups interactions with the same target, in order of first occurrence,
+/// since they share one edge that summarises all of their sites.
+fn group_sites(interactions: Vec<(Interaction, Site)>) -> Vec<(Interaction, Vec<Site>)> {
+    let mut edges: Vec<(Interaction, Vec<Site>)> = Vec::new();
+    let mut positions: HashMap<Interaction, usize> = HashMap::new();
+    for (interaction, site) in interactions {
+        match positions.get(&interaction) {
+            Some(&position) => edges[position].1.push(site),
+            None => {
+                positions.insert(interaction.clone(), edges.len());
+                edges.push((interaction, vec![site]));
+            }
+        }
+    }
+    edges
+}
+
+/// Runs `cypher`, which creates the edge `r` from `caller`, with the
+/// properties summarising the `sites` the edge stands for.
+async fn record_sites(
+    graph: &Graph,
+    project: &str,
+    caller: &ItemRef<'_>,
+    cypher: Query,
+    sites: &[Site],
+) -> Result<()> {
+    let lines: Vec<i64> = sites.iter().map(|site| site.line as i64).collect();
+    graph
+        .run(
+            caller.bind(
+                cypher
+                    .param("count", sites.len() as i64)
+                    .param("line", lines[0])
+                    .param("lines", lines)
+                    .param("in_macro", sites.iter().all(|site| site.in_macro))
+                    .param("in_loop", sites.iter().any(|site| site.in_loop))
+                    .param("in_closure", sites.iter().any(|site| site.in_closure))
+                    .param("in_async", sites.iter().any(|site| site.in_async))
+                    .param("in_unsafe", sites.iter().any(|site| site.in_unsafe))
+                    .param("behind_try", sites.iter().any(|site| site.behind_try))
+                    .param("project", project),
+            ),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Describes the receiver of a method, e.g. `ref` for `&self`, or `none`
+/// for an associated function without one.
+fn self_kind(sig: &Signature) -> &'static str {
+    match sig.receiver() {
+        None => "none",
+        Some(receiver) if receiver.colon_token.is_some() => "typed",
+        Some(receiver) => match (&receiver.reference, &receiver.mutability) {
+            (Some(_), Some(_)) => "mut_ref",
+            (Some(_), None) => "ref",
+            (None, _) => "value",
+        },
+    }
+}
+
+/// Finds the project type a `syn::Type` written in `module_path` refers to,
+/// looking through references and common wrappers like `Option<T>`,
+/// `Vec<T>`, `Box<T>`, `Rc<T>` and `Arc<T>`.
+fn project_type(
+    ty: &syn::Type,
+    symbols: &SymbolTable,
+    module_path: &str,
+) -> Option<(String, TypeKind)> {
+    match ty {
+        syn::Type::Reference(reference) => project_type(&reference.elem, symbols, module_path),
+        syn::Type::Paren(paren) => project_type(&paren.elem, symbols, module_path),
+        syn::Type::Group(group) => project_type(&group.elem, symbols, module_path),
+        syn::Type::Path(type_path) => {
+            let segments = symbols::path_segments(&type_path.path);
+            if let Some(found) = symbols.type_path(module_path, &segments) {
+                return Some(found);
+            }
+            let segment = type_path.path.segments.last()?;
+            if !matches!(
+                segment.ident.to_string().as_str(),
+                "Option" | "Vec" | "Box" | "Rc" | "Arc"
+            ) {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return None;
+            };
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(inner) => project_type(inner, symbols, module_path),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Finds every project type mentioned anywhere in a `syn::Type`, including
+/// inside generic arguments, tuples, slices and references, so that
+/// `Result<Vec<User>, MyError>` yields both `User` and `MyError`.
+fn project_types_in(
+    ty: &syn::Type,
+    symbols: &SymbolTable,
+    module_path: &str,
+) -> Vec<(String, TypeKind)> {
+    let mut found = Vec::new();
+    collect_project_types(ty, symbols, module_path, &mut found);
+    found
+}
+
+fn collect_project_types(
+    ty: &syn::Type,
+    symbols: &SymbolTable,
+    module_path: &str,
+    found: &mut Vec<(String, TypeKind)>,
+) {
+    match ty {
+        syn::Type::Reference(reference) => {
+            collect_project_types(&reference.elem, symbols, module_path, found)
+        }
+        syn::Type::Paren(paren) => collect_project_types(&paren.elem, symbols, module_path, found),
+        syn::Type::Group(group) => collect_project_types(&group.elem, symbols, module_path, found),
+        syn::Type::Slice(slice) => collect_project_types(&slice.elem, symbols, module_path, found),
+        syn::Type::Array(array) => collect_project_types(&array.elem, symbols, module_path, found),
+        syn::Type::Ptr(ptr) => collect_project_types(&ptr.elem, symbols, module_path, found),
+        syn::Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                collect_project_types(elem, symbols, module_path, found);
+            }
+        }
+        syn::Type::Path(type_path) => {
+            for segment in &type_path.path.segments {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(inner) = arg {
+                            collect_project_types(inner, symbols, module_path, found);
+                        }
+                    }
+                }
+            }
+            let segments = symbols::path_segments(&type_path.path);
+            if let Some((path, kind)) = symbols.type_path(module_path, &segments) {
+                if !found.iter().any(|(existing, _)| *existing == path) {
+                    found.push((path, kind));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Paths of the traits in a list of bounds, e.g. `Display` and `Clone` for
+/// `T: Display + Clone`. Relaxed bounds like `?Sized` are skipped.
+fn bound_traits<'a>(bounds: impl IntoIterator<Item = &'a TypeParamBound>) -> Vec<Vec<String>> {
+    bounds
+        .into_iter()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Trait(trait_bound)
+                if !matches!(trait_bound.modifier, TraitBoundModifier::Maybe(_)) =>
+            {
+                Some(symbols::path_segments(&trait_bound.path))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lifetimes in a list of bounds, e.g. `'a` for `T: Clone + 'a`.
+fn bound_lifetimes<'a>(bounds: impl IntoIterator<Item = &'a TypeParamBound>) -> Vec<String> {
+    bounds
+        .into_iter()
+        .filter_map(|bound| match bound {
+            TypeParamBound::Lifetime(lifetime) => Some(lifetime.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Renders a type or other syntax node as compact source text, e.g.
+/// `Option<Vec<u8>>`.
+///
+/// Token streams print with a space between every token, so spaces next to
+/// brackets, path separators and references are dropped again. Expressions
+/// in braces, such as the const argument in `Foo<{ N < 3 }>`, are left as
+/// they are, since `<` and `>` there are comparisons.
+fn type_text_of(node: &impl ToTokens) -> String {
+    let tokens = node.to_token_stream().to_string();
+    let chars: Vec<char> = tokens.chars().collect();
+    let mut text = String::with_capacity(tokens.len());
+    let mut braces = 0usize;
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '{' => braces += 1,
+            '}' => braces = braces.saturating_sub(1),
+            _ => {}
+        }
+        if c == ' ' && braces == 0 {
+            let glued_to_prev =
+                matches!(text.chars().last(), Some('<' | '(' | '[' | '&' | '*' | ':'));
+            let glued_to_next = match chars.get(i + 1) {
+                Some('<' | '>' | ')' | ']' | ',' | ';' | ':') => true,
+                // `Fn(u8)`, but not `&mut (A, B)` or `dyn (Trait)`.
+                Some('(') => !["mut", "dyn", "impl", "const", "->"]
+                    .iter()
+                    .any(|keyword| text.ends_with(keyword)),
+                _ => false,
+            };
+            if glued_to_prev || glued_to_next {
+                continue;
+            }
+        }
+        text.push(c);
+    }
+    text
+}
+
+/// Where an interaction occurs in a function body.
+#[derive(Clone, Copy, Default)]
+struct Site {
+    line: usize,
+    /// Whether it is inside the arguments of a macro invocation.
+    in_macro: bool,
+    /// Whether it is inside the body or condition of a `loop`, `while`, or
+    /// `for`.
+    in_loop: bool,
+    in_closure: bool,
+    in_async: bool,
+    /// Whether it is inside an `unsafe` block or `unsafe fn`.
+    in_unsafe: bool,
+    /// Whether the result of the call is propagated with `?`.
+    behind_try: bool,
+}
+
+/// Properties summarising the [`Site`]s of every interaction an edge stands
+/// for. `in_macro` holds when every site is in a macro, and the other flags
+/// when any site is in that context.
+const SITE_PROPERTIES: &str = "
+    SET r.count = $count, r.lines = $lines, r.line = $line, r.in_macro = $in_macro,
+        r.in_loop = $in_loop, r.in_closure = $in_closure, r.in_async = $in_async,
+        r.in_unsafe = $in_unsafe, r.behind_try = $behind_try
+";
+
+/// The names introduced by one scope of a function body.
+#[derive(Default)]
+struct Scope {
+    /// Names bound by parameters, patterns, and nested functions, with the
+    /// project type of each local variable or parameter whose type is known.
+    /// A call like `f()` may refer to one of them instead of an item.
+    bindings: HashMap<String, Option<String>>,
+    /// Names brought into scope by `use` declarations in the block.
+    imports: Vec<Import>,
+    /// Names of `macro_rules!` macros defined in the block, which have no
+    /// node in the graph.
+    macros: HashSet<String>,
+}
+
+/// Collects the interactions in a function body, each paired with the site
+/// it occurs at.
+///
+/// The traversal reaches every expression and statement, including `match`
+/// arms, loops, closures, `async` blocks, and the bodies of items nested
+/// inside the function, whose interactions are credited to the function.
+///
+/// To resolve method calls, the finder tracks the project types of `self`,
+/// of parameters, and of `let` bindings that have a type annotation or are
+/// initialised from a struct literal or a constructor call like
+/// `Type::new()` or `Type::load()?`.
+///
+/// Paths are resolved through the `use` declarations of the enclosing blocks
+/// before those of the module.
+struct InteractionFinder<'a> {
+    symbols: &'a SymbolTable,
+    /// The module the body is in, which paths are resolved from.
+    module_path: &'a str,
+    self_type: Option<&'a str>,
+    /// The enclosing scopes, innermost last.
+    scopes: Vec<Scope>,
+    /// The context the traversal is currently in, as a site with no line.
+    context: Site,
+    /// Set just before visiting a call whose result is propagated with `?`.
+    behind_try: bool,
+    interactions: Vec<(Interaction, Site)>,
+}
+
+impl<'a> InteractionFinder<'a> {
+    fn new(
+        symbols: &'a SymbolTable,
+        module_path: &'a str,
+        self_type: Option<&'a str>,
+        sig: &Signature,
+    ) -> Self {
+        let mut finder = InteractionFinder {
+            symbols,
+            module_path,
+            self_type,
+            scopes: vec![Scope::default()],
+            context: Site {
+                in_unsafe: sig.unsafety.is_some(),
+                ..Site::default()
+            },
+            behind_try: false,
+            interactions: Vec::new(),
+        };
+        for input in &sig.inputs {
+            if let FnArg::Typed(PatType { pat, ty, .. }) = input {
+                if let Pat::Ident(binding) = &**pat {
+                    let type_name = finder.named_type(ty);
+                    finder.bind(binding.ident.to_string(), type_name);
+                }
+            }
+        }
+        finder
+    }
+
+    /// Binds `name` in the innermost scope, hiding any outer binding of the
+    /// same name along with its type.
+    fn bind(&mut self, name: String, type_name: Option<String>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.bindings.insert(name, type_name);
+        }
+    }
+
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes
+            .iter()
+            .any(|scope| scope.bindings.contains_key(name))
+    }
+
+    /// The project type of the innermost binding of `name`, if known.
+    fn local_type(&self, name: &str) -> Option<String> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.bindings.get(name))
+            .cloned()
+            .flatten()
+    }
+
+    /// Runs `visit` in a new scope, whose bindings and imports end with it.
+    fn scoped(&mut self, visit: impl FnOnce(&mut Self)) {
+        self.scopes.push(Scope::default());
+        visit(self);
+        self.scopes.pop();
+    }
+
+    /// Whether a bare `name` may be a variant brought into scope by a `use`
+    /// in the body or module. Most names in patterns are plain bindings, so
+    /// this is checked before resolving them.
+    fn may_name_variant(&self, name: &str) -> bool {
+        if !self.symbols.is_variant_name(name) {
+            return false;
+        }
+        let imported_in_body = self.scopes.iter().any(|scope| {
+            scope
+                .imports
+                .iter()
+                .any(|import| import.glob || import.name() == Some(name))
+        });
+        imported_in_body || self.symbols.may_import(self.module_path, name)
+    }
+
+    /// Rewrites a path whose first segment is imported by a `use` inside the
+    /// body, innermost block first, e.g. `helper` to `utils::helper` after
+    /// `use utils::helper;`. Returns `None` for any other path.
+    fn block_import(&self, segments: &[String]) -> Option<Vec<String>> {
+        let (first, rest) = segments.split_first()?;
+        let joined = |prefix: &[String]| prefix.iter().chain(rest).cloned().collect();
+        for scope in self.scopes.iter().rev() {
+            for import in scope.imports.iter().filter(|import| !import.glob) {
+                if import.name() == Some(first.as_str()) {
+                    return Some(joined(&import.segments));
+                }
+            }
+            for import in scope.imports.iter().filter(|import| import.glob) {
+                let mut candidate = import.segments.clone();
+                candidate.push(first.clone());
+                let resolved = self.symbols.resolve(self.module_path, &candidate);
+                if !matches!(resolved, Resolution::External(_)) {
+                    return Some(joined(&candidate));
+                }
+            }
+        }
+        None
+    }
+
+    /// The segments of a path written in the body, with any block-level
+    /// import applied; see [`InteractionFinder::block_import`].
+    fn expand(&self, path: &syn::Path) -> Vec<String> {
+        let segments = symbols::path_segments(path);
+        self.block_import(&segments).unwrap_or(segments)
+    }
+
+    /// The project type named by `ty`, looking through references, e.g.
+    /// `my_crate::User` for `&mut User`.
+    fn named_type(&self, ty: &syn::Type) -> Option<String> {
+        match ty {
+            syn::Type::Reference(reference) => self.named_type(&reference.elem),
+            syn::Type::Paren(paren) => self.named_type(&paren.elem),
+            syn::Type::Group(group) => self.named_type(&group.elem),
+            syn::Type::Path(type_path) => self.path_type(&type_path.path),
+            _ => None,
+        }
+    }
+
+    /// The project type named by `path`, or the type of `self` for `Self`.
+    fn path_type(&self, path: &syn::Path) -> Option<String> {
+        self.owner_type(&self.expand(path))
+            .filter(|owner| matches!(self.symbols.kind(owner), Some(ItemKind::Type(_))))
+    }
+
+    /// The project type an expression evaluates to, if it can be told from
+    /// the expression alone.
+    fn expr_type(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Path(ExprPath { path, .. }) => {
+                let ident = path.get_ident()?;
+                if ident == "self" {
+                    self.self_type.map(str::to_string)
+                } else {
+                    self.local_type(&ident.to_string())
+                }
+            }
+            Expr::Struct(expr_struct) => self.path_type(&expr_struct.path),
+            Expr::Field(field) => {
+                let (owner, name) = self.field_of(field)?;
+                self.symbols.field(&owner, &name)?.type_path.clone()
+            }
+            Expr::Call(call) => self.constructed_type(call, false),
+            Expr::Reference(reference) => self.expr_type(&reference.expr),
+            Expr::Paren(paren) => self.expr_type(&paren.expr),
+            Expr::Try(expr_try) => {
+                let mut inner = &*expr_try.expr;
+                while let Expr::Await(ExprAwait { base, .. })
+                | Expr::Paren(ExprParen { expr: base, .. }) = inner
+                {
+                    inner = base;
+                }
+                match inner {
+                    Expr::Call(call) => self.constructed_type(call, true),
+                    _ => self.expr_type(&expr_try.expr),
+                }
+            }
+            Expr::Await(expr_await) => self.expr_type(&expr_await.base),
+            _ => None,
+        }
+    }
+
+    /// The type `call` constructs, if it calls an associated function such
+    /// as `User::new()` that returns its own type, or, when `unwrapped` by
+    /// `?`, a `Result` or `Option` of it.
+    fn constructed_type(&self, call: &ExprCall, unwrapped: bool) -> Option<String> {
+        let Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) = &*call.func
+        else {
+            return None;
+        };
+        let segments = self.expand(path);
+        let (name, owner_segments) = segments.split_last()?;
+        let owner = self.owner_type(owner_segments)?;
+        let returned = self
+            .symbols
+            .return_type(self.symbols.method(&owner, name)?)?;
+        (returned.type_path == owner && returned.wrapped == unwrapped).then_some(owner)
+    }
+
+    /// Resolves the path of a called function through the scopes of the
+    /// body and the module.
+    ///
+    /// Paths that resolve outside the project, such as `Vec::new` or
+    /// `drop`, and calls of local closures are ignored. Bare names that are
+    /// not in scope at all are kept as unresolved.
+    fn callee(&self, callee: &ExprPath) -> Option<Interaction> {
+        let written = symbols::path_segments(&callee.path);
+        if let [name] = written.as_slice() {
+            if callee.qself.is_none() && self.is_bound(name) {
+                return None;
+            }
+        }
+
+        // `<T as Trait>::f` calls the trait's method; `<T>::f` the type's.
+        if let Some(qself) = &callee.qself {
+            let owner = match qself.position {
+                0 => self.named_type(&qself.ty),
+                position => {
+                    let trait_segments = &written[..position];
+                    let trait_segments = self
+                        .block_import(trait_segments)
+                        .unwrap_or_else(|| trait_segments.to_vec());
+                    self.owner_type(&trait_segments)
+                }
+            }?;
+            return self.associated_call(&owner, written.last()?);
+        }
+        if let Some(variant) = self.variant_of(&callee.path) {
+            return Some(Interaction::VariantConstruction(variant));
+        }
+        let imported = self.block_import(&written);
+        let in_block_scope = imported.is_some();
+        let segments = imported.unwrap_or(written);
+        let (name, owner_segments) = segments.split_last()?;
+        if let Some((path, _)) = self.symbols.lookup(self.module_path, &segments, |kind| {
+            kind == ItemKind::Function
+        }) {
+            return Some(Interaction::FunctionCall(path));
+        }
+        if owner_segments.is_empty() {
+            let in_scope = in_block_scope || self.symbols.is_in_scope(self.module_path, name);
+            return (!in_scope).then(|| Interaction::UnresolvedCall(name.clone()));
+        }
+        let owner = self.owner_type(owner_segments)?;
+        self.associated_call(&owner, name)
+    }
+
+    /// The `symbol_id` of the variant a path like `Status::Active` or
+    /// `Self::Active` refers to. Other names under an enum, such as
+    /// `Status::new`, are not variants.
+    fn variant_of(&self, path: &syn::Path) -> Option<String> {
+        let segments = self.expand(path);
+        if let Resolution::Variant { enum_path, name } =
+            self.symbols.resolve(self.module_path, &segments)
+        {
+            return Some(format!("{}::{}", enum_path, name));
+        }
+        // `Self::Active`, or an enum reached through an alias.
+        let (name, enum_segments) = segments.split_last()?;
+        let variant = format!("{}::{}", self.owner_type(enum_segments)?, name);
+        self.symbols.is_variant(&variant).then_some(variant)
+    }
+
+    /// The full path of the project type or trait named by a path such as
+    /// `Self`, `models::User`, or `crate::Shape`.
+    fn owner_type(&self, segments: &[String]) -> Option<String> {
+        if let [segment] = segments {
+            if segment == "Self" {
+                return self.self_type.map(str::to_string);
+            }
+        }
+        self.symbols
+            .lookup(self.module_path, segments, |kind| {
+                matches!(kind, ItemKind::Type(_) | ItemKind::Trait)
+            })
+            .map(|(path, _)| path)
+    }
+
+    /// A call to `owner::name`, if the project defines that method.
+    fn associated_call(&self, owner: &str, name: &str) -> Option<Interaction> {
+        self.symbols
+            .method(owner, name)
+            .map(|id| Interaction::AssociatedCall(id.to_string()))
+    }
+
+    /// The project value a path such as `MAX_USERS` or `config::COUNTER`
+    /// refers to.
+    fn value_of(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Path(ExprPath { path, .. }) => self
+                .symbols
+                .lookup(self.module_path, &self.expand(path), |kind| {
+                    matches!(kind, ItemKind::Value(_))
+                })
+                .map(|(path, _)| path),
+            Expr::Paren(paren) => self.value_of(&paren.expr),
+            _ => None,
+        }
+    }
+
+    /// The project type that owns the field accessed by `field`, and the
+    /// field's name, if the base's type is known and has that field.
+    fn field_of(&self, field: &ExprField) -> Option<(String, String)> {
+        let owner = self.expr_type(&field.base)?;
+        let name = match &field.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+        self.symbols.field(&owner, &name)?;
+        Some((owner, name))
+    }
+
+    /// Records a write to `target` if it is a field of a project type, and
+    /// returns whether it was.
+    fn push_field_write(&mut self, target: &Expr) -> bool {
+        let Expr::Field(field) = target else {
+            return false;
+        };
+        match self.field_of(field) {
+            Some((owner, name)) => {
+                let id = format!("{}::{}", owner, name);
+                self.push(Interaction::FieldWrite(id), field);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn push(&mut self, interaction: Interaction, node: &impl Spanned) {
+        let site = Site {
+            line: node.span().start().line,
+            ..self.context
+        };
+        self.interactions.push((interaction, site));
+    }
+
+    /// Records a call, which may be behind a `?`.
+    fn push_call(&mut self, interaction: Interaction, node: &impl Spanned) {
+        let site = Site {
+            line: node.span().start().line,
+            behind_try: std::mem::take(&mut self.behind_try),
+            ..self.context
+        };
+        self.interactions.push((interaction, site));
+    }
+
+    /// Visits `f` with the context changed by `enter`, restoring it after.
+    fn within(&mut self, enter: impl FnOnce(&mut Site), f: impl FnOnce(&mut Self)) {
+        let outer = self.context;
+        enter(&mut self.context);
+        f(self);
+        self.context = outer;
+    }
+}
+
+impl<'ast> Visit<'ast> for InteractionFinder<'_> {
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|finder| {
+            // Items are in scope throughout the block they are declared in.
+            for stmt in &block.stmts {
+                match stmt {
+                    Stmt::Item(Item::Fn(item_fn)) => {
+                        finder.bind(item_fn.sig.ident.to_string(), None);
+                    }
+                    Stmt::Item(Item::Use(item_use)) => {
+                        if let Some(scope) = finder.scopes.last_mut() {
+                            scope
+                                .imports
+                                .extend(imports::flatten_use_tree(&item_use.tree));
+                        }
+                    }
+                    Stmt::Item(Item::Macro(ItemMacro {
+                        ident: Some(ident), ..
+                    })) => {
+                        if let Some(scope) = finder.scopes.last_mut() {
+                            scope.macros.insert(ident.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            visit::visit_block(finder, block);
+        });
+    }
+
+    fn visit_item(&mut self, item: &'ast Item) {
+        // The parameters of nested functions are not bound outside them.
+        self.scoped(|finder| visit::visit_item(finder, item));
+    }
+
+    fn visit_arm(&mut self, arm: &'ast Arm) {
+        self.scoped(|finder| visit::visit_arm(finder, arm));
+    }
+
+    fn visit_expr_if(&mut self, expr_if: &'ast ExprIf) {
+        // Bindings of an `if let` end with the `if`.
+        self.scoped(|finder| visit::visit_expr_if(finder, expr_if));
+    }
+
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        // A bare name that is a variant in scope, such as `Active` after
+        // `use Status::*;`, matches that variant rather than binding.
+        let simple = pat.by_ref.is_none() && pat.mutability.is_none() && pat.subpat.is_none();
+        if simple && self.may_name_variant(&pat.ident.to_string()) {
+            if let Some(variant) = self.variant_of(&syn::Path::from(pat.ident.clone())) {
+                self.push(Interaction::VariantMatch(variant), pat);
+                return;
+            }
+        }
+        self.bind(pat.ident.to_string(), None);
+        visit::visit_pat_ident(self, pat);
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        // The initialiser is visited first, as it cannot see the binding.
+        if let Some(init) = &local.init {
+            self.visit_expr(&init.expr);
+            if let Some((_, diverge)) = &init.diverge {
+                self.visit_expr(diverge);
+            }
+        }
+        self.visit_pat(&local.pat);
+
+        let (binding, type_name) = match &local.pat {
+            Pat::Type(pat_type) => (&*pat_type.pat, self.named_type(&pat_type.ty)),
+            pat => (
+                pat,
+                local
+                    .init
+                    .as_ref()
+                    .and_then(|init| self.expr_type(&init.expr)),
+            ),
+        };
+        // The pattern has just bound the name in the innermost scope.
+        if let Pat::Ident(binding) = binding {
+            self.bind(binding.ident.to_string(), type_name);
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        let receiver = self.expr_type(&call.receiver);
+        self.push_call(
+            Interaction::MethodCall {
+                name: call.method.to_string(),
+                receiver,
+            },
+            &call.method,
+        );
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        match &*call.func {
+            Expr::Path(callee) => match self.callee(callee) {
+                Some(interaction) => self.push_call(interaction, call),
+                None => self.behind_try = false,
+            },
+            func => {
+                self.behind_try = false;
+                self.visit_expr(func);
+            }
+        }
+        for arg in &call.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_expr_struct(&mut self, expr_struct: &'ast ExprStruct) {
+        if let Some(variant) = self.variant_of(&expr_struct.path) {
+            self.push(Interaction::VariantConstruction(variant), expr_struct);
+        } else if let Some(type_path) = self.path_type(&expr_struct.path) {
+            self.push(Interaction::StructInstantiation(type_path), expr_struct);
+        }
+        visit::visit_expr_struct(self, expr_struct);
+    }
+
+    fn visit_expr_path(&mut self, expr_path: &'ast ExprPath) {
+        if let Some(variant) = self.variant_of(&expr_path.path) {
+            self.push(Interaction::VariantConstruction(variant), expr_path);
+        } else if let Some(value) = self.value_of(&Expr::Path(expr_path.clone())) {
+            self.push(Interaction::ValueRead(value), expr_path);
+        }
+    }
+
+    fn visit_pat(&mut self, pat: &'ast Pat) {
+        // Unit variants in patterns are paths, which would otherwise be
+        // visited as expressions.
+        if let Pat::Path(pat_path) = pat {
+            if let Some(variant) = self.variant_of(&pat_path.path) {
+                self.push(Interaction::VariantMatch(variant), pat_path);
+                return;
+            }
+        }
+        visit::visit_pat(self, pat);
+    }
+
+    fn visit_pat_tuple_struct(&mut self, pat: &'ast PatTupleStruct) {
+        if let Some(variant) = self.variant_of(&pat.path) {
+            self.push(Interaction::VariantMatch(variant), pat);
+        }
+        visit::visit_pat_tuple_struct(self, pat);
+    }
+
+    fn visit_pat_struct(&mut self, pat: &'ast PatStruct) {
+        if let Some(variant) = self.variant_of(&pat.path) {
+            self.push(Interaction::VariantMatch(variant), pat);
+        }
+        visit::visit_pat_struct(self, pat);
+    }
+
+    fn visit_expr_try(&mut self, expr_try: &'ast ExprTry) {
+        let mut inner = &*expr_try.expr;
+        while let Expr::Await(ExprAwait { base, .. }) | Expr::Paren(ExprParen { expr: base, .. }) =
+            inner
+        {
+            inner = base;
+        }
+        self.behind_try = matches!(inner, Expr::Call(_) | Expr::MethodCall(_));
+        self.visit_expr(&expr_try.expr);
+    }
+
+    fn visit_expr_loop(&mut self, expr_loop: &'ast ExprLoop) {
+        self.within(
+            |context| context.in_loop = true,
+            |finder| visit::visit_expr_loop(finder, expr_loop),
+        );
+    }
+
+    fn visit_expr_while(&mut self, expr_while: &'ast ExprWhile) {
+        self.within(
+            |context| context.in_loop = true,
+            |finder| finder.scoped(|finder| visit::visit_expr_while(finder, expr_while)),
+        );
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast ExprForLoop) {
+        // The iterator is evaluated once, before the loop starts.
+        self.visit_expr(&for_loop.expr);
+        self.within(
+            |context| context.in_loop = true,
+            |finder| {
+                finder.scoped(|finder| {
+                    finder.visit_pat(&for_loop.pat);
+                    finder.visit_block(&for_loop.body);
+                })
+            },
+        );
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast ExprClosure) {
+        self.within(
+            |context| context.in_closure = true,
+            |finder| finder.scoped(|finder| visit::visit_expr_closure(finder, closure)),
+        );
+    }
+
+    fn visit_expr_async(&mut self, expr_async: &'ast ExprAsync) {
+        self.within(
+            |context| context.in_async = true,
+            |finder| visit::visit_expr_async(finder, expr_async),
+        );
+    }
+
+    fn visit_expr_unsafe(&mut self, expr_unsafe: &'ast ExprUnsafe) {
+        self.within(
+            |context| context.in_unsafe = true,
+            |finder| visit::visit_expr_unsafe(finder, expr_unsafe),
+        );
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast ExprField) {
+        if let Some((owner, name)) = self.field_of(field) {
+            let id = format!("{}::{}", owner, name);
+            self.push(Interaction::FieldRead(id), field);
+        }
+        visit::visit_expr_field(self, field);
+    }
+
+    fn visit_expr_assign(&mut self, assign: &'ast ExprAssign) {
+        match (&*assign.left, self.value_of(&assign.left)) {
+            (_, Some(value)) => self.push(Interaction::ValueWrite(value), &assign.left),
+            // Only the base of an assigned field is read.
+            (Expr::Field(field), _) if self.push_field_write(&assign.left) => {
+                self.visit_expr(&field.base);
+            }
+            (left, _) => self.visit_expr(left),
+        }
+        self.visit_expr(&assign.right);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &'ast ExprBinary) {
+        // Compound assignments like `COUNTER += 1` both read and write.
+        if is_compound_assignment(&binary.op) {
+            if let Some(value) = self.value_of(&binary.left) {
+                self.push(Interaction::ValueWrite(value), &binary.left);
+            }
+            self.push_field_write(&binary.left);
+        }
+        visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_expr_reference(&mut self, reference: &'ast ExprReference) {
+        match (&*reference.expr, self.value_of(&reference.expr)) {
+            (_, Some(value)) if reference.mutability.is_some() => {
+                self.push(Interaction::ValueWrite(value), reference);
+            }
+            (Expr::Field(field), _)
+                if reference.mutability.is_some() && self.push_field_write(&reference.expr) =>
+            {
+                self.visit_expr(&field.base);
+            }
+            (expr, _) => self.visit_expr(expr),
+        }
+    }
+
+    fn visit_item_macro(&mut self, item_macro: &'ast ItemMacro) {
+        // A `macro_rules!` definition is not an invocation, and its body is
+        // only a pattern of tokens.
+        if item_macro.ident.is_none() {
+            visit::visit_item_macro(self, item_macro);
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let segments = symbols::path_segments(&mac.path);
+        let local = match segments.as_slice() {
+            [name] => self.scopes.iter().any(|scope| scope.macros.contains(name)),
+            _ => false,
+        };
+        if !local {
+            let id = self
+                .symbols
+                .find_macro(self.module_path, &segments)
+                .unwrap_or_else(|| segments.last().cloned().unwrap_or_default());
+            self.push(Interaction::MacroInvocation(id), mac);
+        }
+        // The parsed arguments do not live as long as the syntax tree, but
+        // the finder keeps no references into it.
+        let arguments = macros::macro_arguments(mac);
+        self.within(
+            |context| context.in_macro = true,
+            |finder| {
+                for expr in &arguments.exprs {
+                    Visit::visit_expr(finder, expr);
+                }
+                for pattern in &arguments.patterns {
+                    Visit::visit_pat(finder, pattern);
+                }
+            },
+        );
+    }
+}
+
+/// Whether `op` is an assigning operator such as `+=` or `<<=`.
+fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Indexes `code` as the root file of a crate named `c` and returns the
+    /// interactions found in the function, method or trait default method
+    /// whose `symbol_id` is `function`, e.g. `c::run` or `c::User::save`.
+    fn interactions(code: &str, function: &str) -> Vec<(Interaction, Site)> {
+        let symbols = symbols::test_table(code, crate_tree::TargetKind::Lib);
+        let ast = syn::parse_file(code).expect("test code should parse");
+        let mut pending = vec![("c".to_string(), &ast.items)];
+        while let Some((module_path, items)) = pending.pop() {
+            let find = |caller: &ItemRef, sig, block| {
+                find_interactions(&symbols, &module_path, caller, sig, block)
+            };
+            for item in items {
+                match item {
+                    Item::Mod(item_mod) => {
+                        if let Some((_, items)) = &item_mod.content {
+                            pending.push((format!("{}::{}", module_path, item_mod.ident), items));
+                        }
+                    }
+                    Item::Fn(item_fn) => {
+                        let id = format!("{}::{}", module_path, item_fn.sig.ident);
+                        if id == function {
+                            let caller = ItemRef::Symbol {
+                                label: "Function",
+                                id: &id,
+                            };
+                            return find(&caller, &item_fn.sig, &item_fn.block);
+                        }
+                    }
+                    Item::Impl(item_impl) => {
+                        let syn::Type::Path(self_type) = &*item_impl.self_ty else {
+                            continue;
+                        };
+                        let segments = symbols::path_segments(&self_type.path);
+                        let Some((owner, _)) = symbols.type_path(&module_path, &segments) else {
+                            continue;
+                        };
+                        for impl_item in &item_impl.items {
+                            let ImplItem::Fn(method) = impl_item else {
+                                continue;
+                            };
+                            let id = format!("{}::{}", owner, method.sig.ident);
+                            if id == function {
+                                let caller = ItemRef::Method {
+                                    id: &id,
+                                    owner: &owner,
+                                };
+                                return find(&caller, &method.sig, &method.block);
+                            }
+                        }
+                    }
+                    Item::Trait(item_trait) => {
+                        let owner = format!("{}::{}", module_path, item_trait.ident);
+                        for trait_item in &item_trait.items {
+                            let TraitItem::Fn(method) = trait_item else {
+                                continue;
+                            };
+                            let id = format!("{}::{}", owner, method.sig.ident);
+                            if let Some(block) = method.default.as_ref().filter(|_| id == function)
+                            {
+                                let caller = ItemRef::Method {
+                                    id: &id,
+                                    owner: &owner,
+                                };
+                                return find(&caller, &method.sig, block);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        panic!("no function {} in the test code", function);
+    }
+
+    /// The interactions in `function`, without their sites.
+    fn found(code: &str, function: &str) -> Vec<Interaction> {
+        interactions(code, function)
+            .into_iter()
+            .map(|(interaction, _)| interaction)
+            .collect()
+    }
+
+    fn call(id: &str) -> Interaction {
+        Interaction::FunctionCall(id.to_string())
+    }
+
+    #[test]
+    fn finds_calls_nested_in_arguments() {
+        let code = "
+            fn foo(_: u8) {}
+            fn bar() -> u8 { 0 }
+            fn run() { foo(bar()); }
+        ";
+        assert_eq!(found(code, "c::run"), [call("c::foo"), call("c::bar")]);
+    }
+
+    #[test]
+    fn finds_calls_in_control_flow() {
+        let code = "
+            fn a() -> bool { true }
+            fn b() {}
+            fn c() {}
+            fn d() {}
+            fn e() -> Vec<u8> { Vec::new() }
+            fn run(x: Option<u8>) {
+                match x {
+                    Some(_) if a() => b(),
+                    _ => c(),
+                }
+                loop { d(); break; }
+                while a() {}
+                for _ in e() {}
+            }
+        ";
+        let found = found(code, "c::run");
+        for id in ["c::a", "c::b", "c::c", "c::d", "c::e"] {
+            assert!(found.contains(&call(id)), "missing {}", id);
+        }
+    }
+
+    #[test]
+    fn finds_calls_in_closures_async_blocks_and_literals() {
+        let code = "
+            fn a() -> u8 { 0 }
+            fn b() -> u8 { 0 }
+            fn c() -> u8 { 0 }
+            fn d() -> u8 { 0 }
+            async fn run(items: Vec<u8>) -> (u8, [u8; 1]) {
+                let _ = items.iter().map(|_| a());
+                async { b() }.await;
+                return (c(), [d()]);
+            }
+        ";
+        let found = found(code, "c::run");
+        for id in ["c::a", "c::b", "c::c", "c::d"] {
+            assert!(found.contains(&call(id)), "missing {}", id);
+        }
+    }
+
+    #[test]
+    fn finds_calls_behind_try() {
+        let code = "
+            fn load() -> Result<u8, ()> { Ok(0) }
+            fn run() -> Result<u8, ()> { Ok(load()?) }
+        ";
+        assert_eq!(found(code, "c::run"), [call("c::load")]);
+    }
+
+    #[test]
+    fn resolves_method_receivers_from_self_params_and_bindings() {
+        let code = "
+            struct User;
+            impl User {
+                fn new() -> User { User }
+                fn save(&self) {}
+                fn touch(&self) { self.save(); }
+            }
+            fn run(param: &User) {
+                param.save();
+                let typed: User = make();
+                typed.save();
+                let built = User::new();
+                built.save();
+                let literal = User {};
+                literal.save();
+            }
+            fn make() -> User { User }
+        ";
+        let user_call = Interaction::MethodCall {
+            name: "save".to_string(),
+            receiver: Some("c::User".to_string()),
+        };
+        assert!(found(code, "c::User::touch").contains(&user_call));
+        let calls = found(code, "c::run");
+        assert_eq!(calls.iter().filter(|i| **i == user_call).count(), 4);
+    }
+
+    #[test]
+    fn types_bindings_only_from_calls_returning_their_own_type() {
+        let code = "
+            struct Config;
+            impl Config {
+                fn new() -> Self { Config }
+                fn load() -> Result<Config, ()> { Ok(Config) }
+                fn port_count() -> u16 { 1 }
+                fn go(&self) {}
+            }
+            fn run() -> Result<(), ()> {
+                let n = Config::port_count();
+                n.go();
+                let pending = Config::load();
+                pending.go();
+                let loaded = Config::load()?;
+                loaded.go();
+                let built = Config::new();
+                built.go();
+                Ok(())
+            }
+        ";
+        let receivers: Vec<Option<String>> = found(code, "c::run")
+            .into_iter()
+            .filter_map(|interaction| match interaction {
+                Interaction::MethodCall { receiver, .. } => Some(receiver),
+                _ => None,
+            })
+            .collect();
+        let config = Some("c::Config".to_string());
+        assert_eq!(receivers, [None, None, config.clone(), config]);
+    }
+
+    #[test]
+    fn types_receivers_from_the_innermost_binding() {
+        let code = "
+            struct A;
+            struct B;
+            impl A { fn go(&self) {} }
+            impl B { fn go(&self) {} }
+            fn run() {
+                let x = A {};
+                { let x = B {}; x.go(); }
+                x.go();
+                let _ = |x: u8| x.go();
+            }
+        ";
+        let receivers: Vec<Option<String>> = found(code, "c::run")
+            .into_iter()
+            .filter_map(|interaction| match interaction {
+                Interaction::MethodCall { receiver, .. } => Some(receiver),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            receivers,
+            [Some("c::B".to_string()), Some("c::A".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn types_self_in_trait_default_methods_as_the_trait() {
+        let code = "
+            fn helper() {}
+            trait Shape {
+                fn area(&self) -> f64;
+                fn describe(&self) { self.area(); helper(); }
+            }
+        ";
+        let area = Interaction::MethodCall {
+            name: "area".to_string(),
+            receiver: Some("c::Shape".to_string()),
+        };
+        assert_eq!(found(code, "c::Shape::describe"), [area, call("c::helper")]);
+
+        let symbols = symbols::test_table(code, crate_tree::TargetKind::Lib);
+        let (pattern, key) = method_call_target(&symbols, "area", Some("c::Shape"));
+        assert!(pattern.starts_with("Method"));
+        assert_eq!(key, "c::Shape::area");
+    }
+
+    #[test]
+    fn keeps_method_calls_on_receivers_of_unknown_type() {
+        let code = "
+            fn run(items: Vec<u8>) {
+                let _ = items.iter().map(|x| x + 1);
+                items[0].save();
+            }
+        ";
+        let calls = found(code, "c::run");
+        for name in ["iter", "map", "save"] {
+            let call = Interaction::MethodCall {
+                name: name.to_string(),
+                receiver: None,
+            };
+            assert!(calls.contains(&call), "missing .{}", name);
+        }
+
+        let symbols = SymbolTable::default();
+        let (pattern, key) = method_call_target(&symbols, "save", None);
+        assert!(pattern.starts_with("Unresolved"));
+        assert_eq!(key, ".save");
+    }
+
+    #[test]
+    fn points_method_calls_at_methods_or_receiver_types() {
+        let symbols = symbols::test_table(
+            "
+            #[derive(Clone)]
+            struct User;
+            impl User { fn save(&self) {} }
+            ",
+            crate_tree::TargetKind::Lib,
+        );
+        let (pattern, key) = method_call_target(&symbols, "save", Some("c::User"));
+        assert!(pattern.starts_with("Method"));
+        assert_eq!(key, "c::User::save");
+        let (pattern, key) = method_call_target(&symbols, "clone", Some("c::User"));
+        assert!(pattern.starts_with("Struct"));
+        assert_eq!(key, "c::User");
+    }
+
+    #[test]
+    fn resolves_qualified_calls() {
+        let code = "
+            mod utils { pub fn helper() {} }
+            struct User;
+            impl User { fn new() -> User { User } }
+            trait Shape { fn area(&self) -> f64; }
+            impl Shape for User { fn area(&self) -> f64 { 0.0 } }
+            enum Event { Click(u8) }
+            fn run(user: User) {
+                utils::helper();
+                crate::utils::helper();
+                User::new();
+                <User as Shape>::area(&user);
+                Event::Click(1);
+                Vec::<u8>::new();
+            }
+        ";
+        assert_eq!(
+            found(code, "c::run"),
+            [
+                call("c::utils::helper"),
+                call("c::utils::helper"),
+                Interaction::AssociatedCall("c::User::new".to_string()),
+                Interaction::AssociatedCall("c::Shape::area".to_string()),
+                Interaction::VariantConstruction("c::Event::Click".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_calls_through_block_level_imports() {
+        let code = "
+            mod u { pub fn helper() {} pub fn other() {} }
+            fn run() {
+                use u::helper;
+                helper();
+                {
+                    use u::other as go;
+                    use std::mem::swap;
+                    go();
+                    swap(&mut 1, &mut 2);
+                }
+                { use u::*; other(); }
+                other();
+            }
+        ";
+        assert_eq!(
+            found(code, "c::run"),
+            [
+                call("c::u::helper"),
+                call("c::u::other"),
+                call("c::u::other"),
+                Interaction::UnresolvedCall("other".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_calls_inside_macro_arguments() {
+        let code = "
+            fn name() -> String { String::new() }
+            fn log() {}
+            fn run() {
+                println!(\"{}\", name());
+                log();
+            }
+        ";
+        let interactions = interactions(code, "c::run");
+        let (_, site) = interactions
+            .iter()
+            .find(|(interaction, _)| *interaction == call("c::name"))
+            .expect("call inside println!");
+        assert!(site.in_macro);
+        let (_, site) = interactions
+            .iter()
+            .find(|(interaction, _)| *interaction == call("c::log"))
+            .expect("call outside println!");
+        assert!(!site.in_macro);
+        assert!(interactions
+            .iter()
+            .any(|(interaction, _)| *interaction
+                == Interaction::MacroInvocation("println".to_string())));
+    }
+
+    #[test]
+    fn skips_macros_defined_in_the_body() {
+        let code = "
+            fn helper() -> u8 { 0 }
+            fn run() {
+                macro_rules! m { ($e:expr) => { $e }; }
+                m!(helper());
+                { macro_rules! inner { () => {} } }
+                vec![1];
+            }
+        ";
+        assert_eq!(
+            found(code, "c::run"),
+            [
+                call("c::helper"),
+                Interaction::MacroInvocation("vec".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_field_reads_and_writes() {
+        let code = "
+            struct Counter { count: u32, label: String }
+            impl Counter {
+                fn bump(&mut self) {
+                    self.count += 1;
+                    let _ = &self.label;
+                }
+            }
+            fn run(counter: &mut Counter) {
+                counter.label = String::new();
+                let value = counter.count;
+                let other: Counter = make();
+                let _ = &mut other.count;
+            }
+            fn make() -> Counter { Counter { count: 0, label: String::new() } }
+        ";
+        let read = |name: &str| Interaction::FieldRead(format!("c::Counter::{}", name));
+        let write = |name: &str| Interaction::FieldWrite(format!("c::Counter::{}", name));
+
+        let bump = found(code, "c::Counter::bump");
+        assert!(bump.contains(&write("count")));
+        assert!(bump.contains(&read("label")));
+
+        let run = found(code, "c::run");
+        assert!(run.contains(&write("label")));
+        assert!(!run.contains(&read("label")));
+        assert!(run.contains(&read("count")));
+        assert!(run.contains(&write("count")));
+    }
+
+    #[test]
+    fn finds_variant_construction_and_matches() {
+        let code = "
+            enum Status { Active, Inactive { since: u32 }, Pending(u8) }
+            fn run(status: Status) -> Status {
+                match status {
+                    Status::Active => {}
+                    Status::Inactive { .. } => {}
+                    Status::Pending(_) => {}
+                }
+                if let Status::Active = status {}
+                let Status::Pending(_) = status else { return Status::Active };
+                Status::Pending(1)
+            }
+        ";
+        let variant = |name: &str| format!("c::Status::{}", name);
+        let found = found(code, "c::run");
+        for name in ["Active", "Inactive", "Pending"] {
+            assert!(found.contains(&Interaction::VariantMatch(variant(name))));
+        }
+        assert!(found.contains(&Interaction::VariantConstruction(variant("Active"))));
+        assert!(found.contains(&Interaction::VariantConstruction(variant("Pending"))));
+        assert!(!found.contains(&Interaction::VariantConstruction(variant("Inactive"))));
+    }
+
+    #[test]
+    fn matches_unit_variants_imported_by_glob() {
+        let code = "
+            enum S { A, B }
+            use S::*;
+            fn run(s: S) {
+                match s { A => {}, B => {} }
+                let other = 1;
+            }
+            fn local(s: S) {
+                use S::*;
+                match s { A => {}, ref B => {} }
+            }
+        ";
+        let variant = |name: &str| Interaction::VariantMatch(format!("c::S::{}", name));
+        assert_eq!(found(code, "c::run"), [variant("A"), variant("B")]);
+        assert_eq!(found(code, "c::local"), [variant("A")]);
+    }
+
+    #[test]
+    fn calls_associated_functions_of_enums() {
+        let code = "
+            enum Status { Active }
+            impl Status {
+                fn new() -> Status { Status::Active }
+                fn parse(_: &str) -> Status { Self::new() }
+                fn reset() -> Status { Self::Active }
+            }
+            fn run() -> Status { Status::new() }
+        ";
+        let new = || Interaction::AssociatedCall("c::Status::new".to_string());
+        assert_eq!(found(code, "c::run"), [new()]);
+        assert_eq!(found(code, "c::Status::parse"), [new()]);
+        assert_eq!(
+            found(code, "c::Status::reset"),
+            [Interaction::VariantConstruction(
+                "c::Status::Active".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn does_not_take_function_paths_for_variants() {
+        let code = "
+            enum Status { Active }
+            impl Status { fn parse(_: &str) -> Status { Status::Active } }
+            fn run(names: Vec<&str>) {
+                let _ = names.into_iter().map(Status::parse);
+            }
+        ";
+        assert!(!found(code, "c::run")
+            .iter()
+            .any(|interaction| matches!(interaction, Interaction::VariantConstruction(_))));
+    }
+
+    #[test]
+    fn groups_call_sites_with_their_context() {
+        let code = "
+            fn step() -> Result<(), ()> { Ok(()) }
+            async fn run() -> Result<(), ()> {
+                step()?;
+                for _ in 0..3 { step(); }
+                let _ = || step();
+                async { step() };
+                unsafe { step() };
+                Ok(())
+            }
+        ";
+        let edges = group_sites(interactions(code, "c::run"));
+        let (interaction, sites) = &edges[0];
+        assert_eq!(*interaction, call("c::step"));
+        let lines: Vec<usize> = sites.iter().map(|site| site.line).collect();
+        assert_eq!(lines, [4, 5, 6, 7, 8]);
+        assert!(sites[0].behind_try && !sites[1].behind_try);
+        assert!(sites[1].in_loop && !sites[0].in_loop);
+        assert!(sites[2].in_closure && !sites[1].in_closure);
+        assert!(sites[3].in_async && !sites[2].in_async);
+        assert!(sites[4].in_unsafe && !sites[3].in_unsafe);
+    }
+
+    #[test]
+    fn renders_types_as_compact_text() {
+        for text in [
+            "Option<Vec<u8>>",
+            "&'a mut [u8; 4]",
+            "&mut (A, B)",
+            "*const T",
+            "Box<dyn Fn(u8) -> u8 + Send>",
+            "impl Iterator<Item = &'a str>",
+            "std::collections::HashMap<String, Vec<Self>>",
+            "<T as Shape>::Output",
+            "fn(&str) -> Result<(), Error>",
+            "()",
+            "Foo<{ N < 3 }, { M >> 1 }>",
+        ] {
+            let ty: syn::Type = syn::parse_str(text).unwrap();
+            assert_eq!(type_text_of(&ty), text);
+        }
+    }
+
+    #[test]
+    fn finds_calls_in_nested_functions() {
+        let code = "
+            fn helper() {}
+            fn run() {
+                fn inner() { helper(); }
+                inner();
+            }
+        ";
+        assert_eq!(found(code, "c::run"), [call("c::helper")]);
+    }
+
+    #[test]
+    fn skips_calls_of_local_bindings_within_their_scope() {
+        let code = "
+            fn helper() {}
+            fn run(f: fn()) {
+                f();
+                let helper = || {};
+                helper();
+                let _ = |helper: fn()| helper();
+                match 1 { helper => helper() }
+            }
+        ";
+        assert!(found(code, "c::run").is_empty());
+    }
+
+    #[test]
+    fn resolves_calls_after_the_scope_of_a_binding_ends() {
+        let code = "
+            fn helper() {}
+            fn run() {
+                for helper in 0..3 {}
+                let _ = |helper: u8| helper;
+                match 1 { helper => {} }
+                if let Some(helper) = Some(1) {}
+                { let helper = 1; }
+                fn inner(helper: u8) {}
+                helper();
+                println!(\"{:?}\", helper());
+            }
+        ";
+        assert_eq!(
+            found(code, "c::run"),
+            [
+                call("c::helper"),
+                Interaction::MacroInvocation("println".to_string()),
+                call("c::helper"),
+            ]
+        );
     }
 }
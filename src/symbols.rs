@@ -0,0 +1,1185 @@
+//! A project-wide table of the items defined in the indexed crates.
+//!
+//! The table is built from every loaded file before anything is written to
+//! the graph, so that references to a type can be given the right node label
+//! regardless of the order in which files are processed, and so that paths
+//! like `crate::utils::helper` can be resolved to the item they name.
+
+use std::collections::{HashMap, HashSet};
+
+use syn::{Field, Ident, Item, ItemMacro, Path, Type, Visibility};
+
+use crate::{
+    crate_tree::SourceFile,
+    imports::{self, Import},
+};
+
+/// The kinds of named types that are indexed as graph nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+    Struct,
+    Enum,
+    Union,
+    TypeAlias,
+}
+
+impl TypeKind {
+    /// The Neo4j label used for nodes of this kind.
+    pub fn label(self) -> &'static str {
+        match self {
+            TypeKind::Struct => "Struct",
+            TypeKind::Enum => "Enum",
+            TypeKind::Union => "Union",
+            TypeKind::TypeAlias => "TypeAlias",
+        }
+    }
+}
+
+/// The kinds of named global values that are indexed as graph nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    Const,
+    Static,
+}
+
+/// The kinds of items that a path can name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Module,
+    Function,
+    Trait,
+    Macro,
+    Type(TypeKind),
+    Value(ValueKind),
+}
+
+impl ItemKind {
+    /// The Neo4j label used for nodes of this kind.
+    pub fn label(self) -> &'static str {
+        match self {
+            ItemKind::Module => "Module",
+            ItemKind::Function => "Function",
+            ItemKind::Trait => "Trait",
+            ItemKind::Macro => "Macro",
+            ItemKind::Type(kind) => kind.label(),
+            ItemKind::Value(ValueKind::Const) => "Const",
+            ItemKind::Value(ValueKind::Static) => "Static",
+        }
+    }
+}
+
+/// What a path such as `crate::utils::helper` refers to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// An item defined in the project, identified by its full path.
+    Item { path: String, kind: ItemKind },
+    /// A variant of a project enum, given with the enum's full path.
+    Variant { enum_path: String, name: String },
+    /// Anything outside the project, or a project path that names nothing.
+    External(String),
+}
+
+/// A field of a project struct or union.
+pub struct FieldInfo {
+    /// Full path of the field's type with references removed, e.g.
+    /// `my_crate::Config` for `&'a Config`, if it is a project type.
+    pub type_path: Option<String>,
+}
+
+/// The project type a method returns, which tells the type of a call to it.
+pub struct ReturnedType {
+    /// Full path of the type, with `Self` resolved to the method's owner.
+    pub type_path: String,
+    /// Whether it is wrapped in a `Result` or `Option`, as in
+    /// `fn load() -> Result<Self, Error>`.
+    pub wrapped: bool,
+}
+
+/// The methods of an `impl` block, kept until every type is known so that
+/// its self type and trait can be resolved.
+struct ImplMethods {
+    module_path: String,
+    self_segments: Vec<String>,
+    trait_segments: Option<Vec<String>>,
+    /// Each method's name, with the type it returns.
+    methods: Vec<(String, Option<ReturnedSegments>)>,
+}
+
+/// The path of the type a method returns, as written, and whether it is
+/// wrapped in a `Result` or `Option`.
+type ReturnedSegments = (Vec<String>, bool);
+
+/// A name brought into scope by a `use` declaration.
+struct ScopedImport {
+    import: Import,
+    /// Whether the path starts with `::`, so it is in an external crate.
+    external: bool,
+}
+
+/// What a path means from a given module, before it is looked up.
+#[derive(Clone)]
+enum Scoped {
+    /// A full path into the project, e.g. `my_crate::utils::helper`.
+    Project(String),
+    /// A path outside the project, e.g. `std::collections::HashMap`.
+    External(String),
+    /// A path whose first segment names nothing in scope.
+    Unknown,
+}
+
+/// The imported names already looked up while resolving one path, by
+/// module and name, so that each is followed once however many globs lead
+/// to it. `None` marks a lookup still in progress, which stops cycles of
+/// imports such as `pub use child::*;` with `use super::*;` in the child.
+type ImportLookups = HashMap<(String, String), Option<Scoped>>;
+
+/// Crates that are in scope everywhere without being part of the project.
+const EXTERNAL_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// The names the standard prelude brings into every module, with their
+/// paths.
+const PRELUDE: &[(&str, &str)] = &[
+    ("Option", "std::option::Option"),
+    ("Some", "std::option::Option::Some"),
+    ("None", "std::option::Option::None"),
+    ("Result", "std::result::Result"),
+    ("Ok", "std::result::Result::Ok"),
+    ("Err", "std::result::Result::Err"),
+    ("Box", "std::boxed::Box"),
+    ("String", "std::string::String"),
+    ("Vec", "std::vec::Vec"),
+    ("ToString", "std::string::ToString"),
+    ("ToOwned", "std::borrow::ToOwned"),
+    ("drop", "std::mem::drop"),
+    ("Clone", "std::clone::Clone"),
+    ("Copy", "std::marker::Copy"),
+    ("Send", "std::marker::Send"),
+    ("Sync", "std::marker::Sync"),
+    ("Sized", "std::marker::Sized"),
+    ("Unpin", "std::marker::Unpin"),
+    ("Drop", "std::ops::Drop"),
+    ("Fn", "std::ops::Fn"),
+    ("FnMut", "std::ops::FnMut"),
+    ("FnOnce", "std::ops::FnOnce"),
+    ("Debug", "std::fmt::Debug"),
+    ("Default", "std::default::Default"),
+    ("Hash", "std::hash::Hash"),
+    ("Eq", "std::cmp::Eq"),
+    ("PartialEq", "std::cmp::PartialEq"),
+    ("Ord", "std::cmp::Ord"),
+    ("PartialOrd", "std::cmp::PartialOrd"),
+    ("AsRef", "std::convert::AsRef"),
+    ("AsMut", "std::convert::AsMut"),
+    ("From", "std::convert::From"),
+    ("Into", "std::convert::Into"),
+    ("TryFrom", "std::convert::TryFrom"),
+    ("TryInto", "std::convert::TryInto"),
+    ("Iterator", "std::iter::Iterator"),
+    ("IntoIterator", "std::iter::IntoIterator"),
+    ("DoubleEndedIterator", "std::iter::DoubleEndedIterator"),
+    ("ExactSizeIterator", "std::iter::ExactSizeIterator"),
+    ("Extend", "std::iter::Extend"),
+    ("FromIterator", "std::iter::FromIterator"),
+];
+
+/// Fields whose type is resolved once every type is known, as the module
+/// they are written in, their `Type::field` key and their type's path.
+type PendingField = (String, String, Option<Vec<String>>);
+
+/// The types, global values and other items defined anywhere in the project,
+/// by full path.
+#[derive(Default)]
+pub struct SymbolTable {
+    /// Every item by its full path, e.g. `my_crate::utils::helper`.
+    items: HashMap<String, ItemKind>,
+    /// Full paths of the variants of project enums.
+    variants: HashSet<String>,
+    /// Names of the variants of project enums, e.g. `Active`.
+    variant_names: HashSet<String>,
+    /// Names of the crates in the project, which paths may start with.
+    crates: HashSet<String>,
+    /// Names of the library crates, whose public items other crates can
+    /// name.
+    libraries: HashSet<String>,
+    /// The `symbol_id`s of the methods of project types and traits, by
+    /// `owner::method`, where `owner` is the full path of the type or trait.
+    methods: HashMap<String, String>,
+    /// The project types returned by methods of project types, by the
+    /// method's `symbol_id`.
+    returns: HashMap<String, ReturnedType>,
+    /// Fields of project structs and unions, as `Type::field` or `Type::0`
+    /// with the full path of the type.
+    fields: HashMap<String, FieldInfo>,
+    /// Declared visibility of every item, e.g. `pub(crate)`, by full path.
+    visibilities: HashMap<String, String>,
+    /// The names each module brings into scope with `use` declarations, by
+    /// the module's full path.
+    scopes: HashMap<String, Vec<ScopedImport>>,
+    /// `pub use` declarations, with the module they appear in.
+    reexports: Vec<(String, Import)>,
+    /// Items made reachable from outside their crate by a `pub use` in a
+    /// reachable module.
+    reexported: HashSet<String>,
+}
+
+impl SymbolTable {
+    /// Builds the table from every item in `sources`, including items nested
+    /// in inline modules.
+    pub fn build<'a>(sources: impl IntoIterator<Item = &'a SourceFile>) -> Self {
+        let mut table = SymbolTable::default();
+        let mut impls = Vec::new();
+        let mut fields = Vec::new();
+        for source in sources {
+            if source.parent_module.is_none() {
+                table.crates.insert(source.module_path.clone());
+                if source.crate_kind.is_library() {
+                    table.libraries.insert(source.module_path.clone());
+                }
+            }
+            table
+                .items
+                .insert(source.module_path.clone(), ItemKind::Module);
+            table.add_items(
+                &source.module_path,
+                &source.ast.items,
+                &mut impls,
+                &mut fields,
+            );
+        }
+        table.add_impl_methods(impls);
+        for (module_path, key, segments) in fields {
+            let type_path = segments
+                .and_then(|segments| table.type_path(&module_path, &segments))
+                .map(|(path, _)| path);
+            table.fields.insert(key, FieldInfo { type_path });
+        }
+        table.resolve_reexports();
+        table
+    }
+
+    fn add_items(
+        &mut self,
+        module_path: &str,
+        items: &[Item],
+        impls: &mut Vec<ImplMethods>,
+        fields: &mut Vec<PendingField>,
+    ) {
+        for item in items {
+            let vis = match item {
+                Item::Fn(item) => &item.vis,
+                Item::Trait(item) => &item.vis,
+                Item::Struct(item) => &item.vis,
+                Item::Enum(item) => &item.vis,
+                Item::Union(item) => &item.vis,
+                Item::Type(item) => &item.vis,
+                Item::Const(item) => &item.vis,
+                Item::Static(item) => &item.vis,
+                Item::Mod(item) => &item.vis,
+                Item::Impl(item_impl) => {
+                    if let syn::Type::Path(type_path) = &*item_impl.self_ty {
+                        impls.push(ImplMethods {
+                            module_path: module_path.to_string(),
+                            self_segments: path_segments(&type_path.path),
+                            trait_segments: item_impl
+                                .trait_
+                                .as_ref()
+                                .map(|(_, path, _)| path_segments(path)),
+                            methods: item_impl
+                                .items
+                                .iter()
+                                .filter_map(|impl_item| match impl_item {
+                                    syn::ImplItem::Fn(method) => Some((
+                                        method.sig.ident.to_string(),
+                                        returned_segments(&method.sig.output),
+                                    )),
+                                    _ => None,
+                                })
+                                .collect(),
+                        });
+                    }
+                    continue;
+                }
+                Item::Use(item) => {
+                    for import in imports::flatten_use_tree(&item.tree) {
+                        if let Visibility::Public(_) = item.vis {
+                            self.reexports
+                                .push((module_path.to_string(), import.clone()));
+                        }
+                        self.scopes
+                            .entry(module_path.to_string())
+                            .or_default()
+                            .push(ScopedImport {
+                                import,
+                                // `use ::name` always refers to an external crate.
+                                external: item.leading_colon.is_some(),
+                            });
+                    }
+                    continue;
+                }
+                _ => &Visibility::Inherited,
+            };
+            let (ident, kind) = match item {
+                Item::Fn(item_fn) => (&item_fn.sig.ident, ItemKind::Function),
+                Item::Trait(item_trait) => {
+                    let trait_path = format!("{}::{}", module_path, item_trait.ident);
+                    for trait_item in &item_trait.items {
+                        if let syn::TraitItem::Fn(method) = trait_item {
+                            let id = format!("{}::{}", trait_path, method.sig.ident);
+                            self.methods.insert(id.clone(), id);
+                        }
+                    }
+                    (&item_trait.ident, ItemKind::Trait)
+                }
+                Item::Struct(item_struct) => {
+                    let owner = format!("{}::{}", module_path, item_struct.ident);
+                    add_fields(module_path, &owner, item_struct.fields.iter(), fields);
+                    (&item_struct.ident, ItemKind::Type(TypeKind::Struct))
+                }
+                Item::Enum(item_enum) => {
+                    for variant in &item_enum.variants {
+                        self.variant_names.insert(variant.ident.to_string());
+                        self.variants.insert(format!(
+                            "{}::{}::{}",
+                            module_path, item_enum.ident, variant.ident
+                        ));
+                    }
+                    (&item_enum.ident, ItemKind::Type(TypeKind::Enum))
+                }
+                Item::Union(item_union) => {
+                    let owner = format!("{}::{}", module_path, item_union.ident);
+                    add_fields(module_path, &owner, item_union.fields.named.iter(), fields);
+                    (&item_union.ident, ItemKind::Type(TypeKind::Union))
+                }
+                Item::Type(item_type) => (&item_type.ident, ItemKind::Type(TypeKind::TypeAlias)),
+                Item::Const(item_const) => (&item_const.ident, ItemKind::Value(ValueKind::Const)),
+                Item::Static(item_static) => {
+                    (&item_static.ident, ItemKind::Value(ValueKind::Static))
+                }
+                Item::Macro(item_macro) => match &item_macro.ident {
+                    Some(ident) => {
+                        // Exported macros live at the root of their crate.
+                        if is_exported(item_macro) {
+                            let path = macro_path(module_path, ident);
+                            self.visibilities.insert(path.clone(), "pub".to_string());
+                            self.items.insert(path, ItemKind::Macro);
+                            continue;
+                        }
+                        (ident, ItemKind::Macro)
+                    }
+                    None => continue,
+                },
+                Item::Mod(item_mod) => {
+                    let child_path = format!("{}::{}", module_path, item_mod.ident);
+                    if let Some((_, items)) = &item_mod.content {
+                        self.add_items(&child_path, items, impls, fields);
+                    }
+                    (&item_mod.ident, ItemKind::Module)
+                }
+                _ => continue,
+            };
+
+            let name = ident.to_string();
+            let path = format!("{}::{}", module_path, name);
+            self.visibilities
+                .entry(path.clone())
+                .or_insert_with(|| visibility_text(vis));
+            self.items.insert(path, kind);
+        }
+    }
+
+    /// Registers the methods of `impl` blocks under the full path of their
+    /// self type. Inherent methods take precedence over trait methods of the
+    /// same name, as they do in method resolution.
+    fn add_impl_methods(&mut self, impls: Vec<ImplMethods>) {
+        for block in impls {
+            let Some((owner, _)) = self.type_path(&block.module_path, &block.self_segments) else {
+                continue;
+            };
+            let trait_id = block
+                .trait_segments
+                .map(|segments| self.trait_id(&block.module_path, &segments));
+            for (name, returned) in block.methods {
+                let id = method_id(&owner, trait_id.as_deref(), &name);
+                if let Some((segments, wrapped)) = returned {
+                    let type_path = match segments.as_slice() {
+                        [segment] if segment == "Self" => Some(owner.clone()),
+                        _ => self
+                            .type_path(&block.module_path, &segments)
+                            .map(|(path, _)| path),
+                    };
+                    if let Some(type_path) = type_path {
+                        self.returns
+                            .insert(id.clone(), ReturnedType { type_path, wrapped });
+                    }
+                }
+                let key = format!("{}::{}", owner, name);
+                match trait_id {
+                    Some(_) => {
+                        self.methods.entry(key).or_insert(id);
+                    }
+                    None => {
+                        self.methods.insert(key, id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the field called `name` (or numbered, for tuple structs) of
+    /// the project struct or union at `owner`.
+    pub fn field(&self, owner: &str, name: &str) -> Option<&FieldInfo> {
+        self.fields.get(&format!("{}::{}", owner, name))
+    }
+
+    /// Whether `path` is a variant of a project enum, e.g.
+    /// `my_crate::Status::Active`.
+    pub fn is_variant(&self, path: &str) -> bool {
+        self.variants.contains(path)
+    }
+
+    /// Whether any project enum has a variant called `name`.
+    pub fn is_variant_name(&self, name: &str) -> bool {
+        self.variant_names.contains(name)
+    }
+
+    /// Whether a `use` in `module_path` may bring `name` into scope: one
+    /// imports that name, or the module has a glob import. This is cheap to
+    /// check before resolving the name.
+    pub fn may_import(&self, module_path: &str, name: &str) -> bool {
+        self.scopes.get(module_path).is_some_and(|scope| {
+            scope.iter().any(|scoped| {
+                let import = &scoped.import;
+                import.glob || import.name() == Some(name)
+            })
+        })
+    }
+
+    /// Returns the kind of the project item at `path`, if there is one.
+    pub fn kind(&self, path: &str) -> Option<ItemKind> {
+        self.items.get(path).copied()
+    }
+
+    /// Returns the `symbol_id` of the method called `name` of the project
+    /// type or trait at `owner`, if the project defines one.
+    pub fn method(&self, owner: &str, name: &str) -> Option<&str> {
+        self.methods
+            .get(&format!("{}::{}", owner, name))
+            .map(String::as_str)
+    }
+
+    /// Returns the project type returned by the method with `symbol_id`
+    /// `method_id`, if it is known.
+    pub fn return_type(&self, method_id: &str) -> Option<&ReturnedType> {
+        self.returns.get(method_id)
+    }
+
+    /// Finds the project item a path written inside `module_path` refers
+    /// to, among those whose kind is accepted by `accept`.
+    pub fn lookup(
+        &self,
+        module_path: &str,
+        segments: &[String],
+        accept: impl Fn(ItemKind) -> bool,
+    ) -> Option<(String, ItemKind)> {
+        match self.resolve(module_path, segments) {
+            Resolution::Item { path, kind } if accept(kind) => Some((path, kind)),
+            _ => None,
+        }
+    }
+
+    /// Finds the project macro invoked as `segments!` inside `module_path`.
+    ///
+    /// `macro_rules!` macros are scoped by their place in the source rather
+    /// than by path, so a bare name is looked up in the module and each of
+    /// its ancestors before being resolved as a path.
+    pub fn find_macro(&self, module_path: &str, segments: &[String]) -> Option<String> {
+        if let [name] = segments {
+            let mut module = Some(module_path);
+            while let Some(current) = module {
+                let path = format!("{}::{}", current, name);
+                if self.kind(&path) == Some(ItemKind::Macro) {
+                    return Some(path);
+                }
+                module = current.rsplit_once("::").map(|(parent, _)| parent);
+            }
+        }
+        self.lookup(module_path, segments, |kind| kind == ItemKind::Macro)
+            .map(|(path, _)| path)
+    }
+
+    /// Whether a bare name means anything inside `module_path`: an item of
+    /// the module, an imported name, a crate, or a name from the prelude.
+    pub fn is_in_scope(&self, module_path: &str, name: &str) -> bool {
+        !matches!(
+            self.resolve_name(module_path, name, &mut HashMap::new()),
+            Scoped::Unknown
+        )
+    }
+
+    /// Finds the project type a path written inside `module_path` refers
+    /// to; see [`SymbolTable::lookup`].
+    pub fn type_path(&self, module_path: &str, segments: &[String]) -> Option<(String, TypeKind)> {
+        match self.lookup(module_path, segments, |kind| {
+            matches!(kind, ItemKind::Type(_))
+        })? {
+            (path, ItemKind::Type(kind)) => Some((path, kind)),
+            _ => None,
+        }
+    }
+
+    /// The `symbol_id` of the trait a path written inside `module_path`
+    /// refers to: its full path for project traits, and its name for traits
+    /// defined elsewhere, such as `Debug` or `serde::Serialize`.
+    pub fn trait_id(&self, module_path: &str, segments: &[String]) -> String {
+        match self.lookup(module_path, segments, |kind| kind == ItemKind::Trait) {
+            Some((path, _)) => path,
+            None => segments.last().cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Returns the declared visibility of the item at `path`. Crate roots are
+    /// `pub`, and anything unknown is `private`.
+    pub fn visibility(&self, path: &str) -> &str {
+        if self.crates.contains(path) {
+            return "pub";
+        }
+        self.visibilities
+            .get(path)
+            .map_or("private", String::as_str)
+    }
+
+    /// Whether the item at `path` can be named from outside its crate: it is
+    /// `pub` in a module that is itself reachable, or re-exported with
+    /// `pub use` from one. Only the roots of library crates are reachable,
+    /// as no other crate can depend on a binary, example, test or bench.
+    pub fn is_effectively_public(&self, path: &str) -> bool {
+        if self.libraries.contains(path) || self.reexported.contains(path) {
+            return true;
+        }
+        match path.rsplit_once("::") {
+            Some((parent, _)) => {
+                self.visibility(path) == "pub" && self.is_effectively_public(parent)
+            }
+            None => false,
+        }
+    }
+
+    /// Follows `pub use` declarations in reachable modules until no more
+    /// items become reachable, since re-exports can be chained.
+    fn resolve_reexports(&mut self) {
+        loop {
+            let mut found = Vec::new();
+            for (module_path, import) in &self.reexports {
+                if !self.is_effectively_public(module_path) {
+                    continue;
+                }
+                let Resolution::Item { path, .. } = self.resolve(module_path, &import.segments)
+                else {
+                    continue;
+                };
+                if import.glob {
+                    // A glob re-exports the `pub` items directly inside.
+                    let prefix = format!("{}::", path);
+                    found.extend(
+                        self.items
+                            .keys()
+                            .filter(|item| {
+                                item.strip_prefix(&prefix)
+                                    .is_some_and(|rest| !rest.contains("::"))
+                                    && self.visibility(item) == "pub"
+                            })
+                            .cloned(),
+                    );
+                } else {
+                    found.push(path);
+                }
+            }
+            let before = self.reexported.len();
+            self.reexported.extend(found);
+            if self.reexported.len() == before {
+                break;
+            }
+        }
+    }
+
+    /// Resolves a path written inside the module at `module_path`.
+    ///
+    /// Paths may start with `crate`, `self`, `super`, the name of a crate in
+    /// the project, or a name in scope in the current module, including
+    /// imported names. Anything else is treated as external.
+    pub fn resolve(&self, module_path: &str, segments: &[String]) -> Resolution {
+        let full_path = match self.absolute_path(module_path, segments, &mut HashMap::new()) {
+            Scoped::Project(full_path) => full_path,
+            Scoped::External(path) => return Resolution::External(path),
+            Scoped::Unknown => return Resolution::External(segments.join("::")),
+        };
+        if let Some(&kind) = self.items.get(&full_path) {
+            return Resolution::Item {
+                path: full_path,
+                kind,
+            };
+        }
+        if self.variants.contains(&full_path) {
+            if let Some((enum_path, name)) = full_path.rsplit_once("::") {
+                return Resolution::Variant {
+                    enum_path: enum_path.to_string(),
+                    name: name.to_string(),
+                };
+            }
+        }
+        Resolution::External(full_path)
+    }
+
+    /// Turns a path written inside `module_path` into a full path starting
+    /// with a crate name, or the path it names outside the project.
+    fn absolute_path(
+        &self,
+        module_path: &str,
+        segments: &[String],
+        lookups: &mut ImportLookups,
+    ) -> Scoped {
+        let Some((first, rest)) = segments.split_first() else {
+            return Scoped::Unknown;
+        };
+        let module: Vec<&str> = module_path.split("::").collect();
+        let mut base: Vec<String> = match first.as_str() {
+            "crate" | "$crate" => vec![module[0].to_string()],
+            "self" => module.iter().map(|s| s.to_string()).collect(),
+            "super" => module[..module.len() - 1]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            name => match self.resolve_name(module_path, name, lookups) {
+                Scoped::Project(path) => vec![path],
+                Scoped::External(path) => {
+                    let mut path = vec![path];
+                    path.extend(rest.iter().cloned());
+                    return Scoped::External(path.join("::"));
+                }
+                Scoped::Unknown => return Scoped::Unknown,
+            },
+        };
+        for (i, segment) in rest.iter().enumerate() {
+            match segment.as_str() {
+                "super" => {
+                    base.pop();
+                }
+                "self" => {}
+                name => {
+                    let module = base.join("::");
+                    base.push(name.to_string());
+                    // `crate::a::b` may name something `a` imports rather
+                    // than defines, as with a chain of `pub use`.
+                    let defined = self.items.contains_key(&base.join("::"));
+                    if defined || self.kind(&module) != Some(ItemKind::Module) {
+                        continue;
+                    }
+                    match self.resolve_import(&module, name, lookups) {
+                        Scoped::Project(path) => base = vec![path],
+                        Scoped::External(path) => {
+                            let mut path = vec![path];
+                            path.extend(rest[i + 1..].iter().cloned());
+                            return Scoped::External(path.join("::"));
+                        }
+                        Scoped::Unknown => {}
+                    }
+                }
+            }
+        }
+        if base.is_empty() {
+            Scoped::Unknown
+        } else {
+            Scoped::Project(base.join("::"))
+        }
+    }
+
+    /// Resolves the first segment of a path written inside `module_path`,
+    /// in Rust's order of precedence: items defined in the module, then
+    /// names imported by `use`, then glob imports, then crate names, then the
+    /// standard prelude.
+    ///
+    /// `lookups` holds the imports already followed for the path being
+    /// resolved, as an import can name another import.
+    fn resolve_name(&self, module_path: &str, name: &str, lookups: &mut ImportLookups) -> Scoped {
+        let local = format!("{}::{}", module_path, name);
+        if self.items.contains_key(&local) {
+            return Scoped::Project(local);
+        }
+        match self.resolve_import(module_path, name, lookups) {
+            Scoped::Unknown => {}
+            resolved => return resolved,
+        }
+        if self.crates.contains(name) {
+            return Scoped::Project(name.to_string());
+        }
+        if EXTERNAL_CRATES.contains(&name) {
+            return Scoped::External(name.to_string());
+        }
+        match PRELUDE
+            .iter()
+            .find(|(prelude_name, _)| *prelude_name == name)
+        {
+            Some((_, path)) => Scoped::External(path.to_string()),
+            None => Scoped::Unknown,
+        }
+    }
+
+    /// Resolves a name brought into `module_path` by its `use` declarations,
+    /// explicit imports before globs.
+    fn resolve_import(&self, module_path: &str, name: &str, lookups: &mut ImportLookups) -> Scoped {
+        let key = (module_path.to_string(), name.to_string());
+        match lookups.get(&key) {
+            Some(Some(resolved)) => return resolved.clone(),
+            Some(None) => return Scoped::Unknown,
+            None => {}
+        }
+        lookups.insert(key.clone(), None);
+        let resolved = self.follow_imports(module_path, name, lookups);
+        lookups.insert(key, Some(resolved.clone()));
+        resolved
+    }
+
+    /// Looks `name` up in the `use` declarations of `module_path`; see
+    /// [`SymbolTable::resolve_import`].
+    fn follow_imports(&self, module_path: &str, name: &str, lookups: &mut ImportLookups) -> Scoped {
+        let scope = self.scopes.get(module_path).map_or(&[][..], Vec::as_slice);
+        for scoped in scope.iter().filter(|scoped| !scoped.import.glob) {
+            let import = &scoped.import;
+            if import.name() != Some(name) {
+                continue;
+            }
+            // `use serde;` names the crate rather than the import itself.
+            if scoped.external || import.segments == [name] {
+                return Scoped::External(import.segments.join("::"));
+            }
+            return match self.absolute_path(module_path, &import.segments, lookups) {
+                // A `use` path that starts with an unknown name is taken to
+                // be in an external crate.
+                Scoped::Unknown => Scoped::External(import.segments.join("::")),
+                resolved => resolved,
+            };
+        }
+        for scoped in scope.iter().filter(|scoped| scoped.import.glob) {
+            if scoped.external {
+                continue;
+            }
+            let Scoped::Project(path) =
+                self.absolute_path(module_path, &scoped.import.segments, lookups)
+            else {
+                continue;
+            };
+            // Globs of a module bring in what is in scope there, including
+            // its own imports, as with `use super::*;` in a `tests` module.
+            if self.kind(&path) == Some(ItemKind::Module) {
+                match self.resolve_name(&path, name, lookups) {
+                    Scoped::Unknown => continue,
+                    resolved => return resolved,
+                }
+            }
+            let candidate = format!("{}::{}", path, name);
+            if self.variants.contains(&candidate) {
+                return Scoped::Project(candidate);
+            }
+        }
+        Scoped::Unknown
+    }
+}
+
+/// The `symbol_id` of a method of the type at `owner`: `owner::name` for
+/// inherent methods, and `<owner as Trait>::name` for trait impl methods, so
+/// that impls of different traits with a method of the same name, like
+/// `fmt`, stay apart.
+pub fn method_id(owner: &str, trait_id: Option<&str>, name: &str) -> String {
+    match trait_id {
+        Some(trait_id) => format!("<{} as {}>::{}", owner, trait_id, name),
+        None => format!("{}::{}", owner, name),
+    }
+}
+
+/// Whether a `macro_rules!` definition is `#[macro_export]`ed.
+pub fn is_exported(item_macro: &ItemMacro) -> bool {
+    item_macro
+        .attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("macro_export"))
+}
+
+/// The full path of the exported macro `ident` defined in `module_path`,
+/// which lives at the root of its crate.
+pub fn macro_path(module_path: &str, ident: &Ident) -> String {
+    let root = module_path.split("::").next().unwrap_or(module_path);
+    format!("{}::{}", root, ident)
+}
+
+/// The path of the type named by a return type, looking through references
+/// and through a `Result` or `Option` around it, along with whether it was
+/// wrapped in one.
+fn returned_segments(output: &syn::ReturnType) -> Option<ReturnedSegments> {
+    let syn::ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let mut ty = &**ty;
+    while let Type::Reference(reference) = ty {
+        ty = &reference.elem;
+    }
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let last = type_path.path.segments.last()?;
+    if last.ident == "Result" || last.ident == "Option" {
+        if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+            if let Some(syn::GenericArgument::Type(Type::Path(inner))) = args.args.first() {
+                return Some((path_segments(&inner.path), true));
+            }
+        }
+    }
+    Some((path_segments(&type_path.path), false))
+}
+
+/// Queues the fields of the type at `owner` for their types to be resolved.
+fn add_fields<'a>(
+    module_path: &str,
+    owner: &str,
+    fields: impl Iterator<Item = &'a Field>,
+    pending: &mut Vec<PendingField>,
+) {
+    for (index, field) in fields.enumerate() {
+        let name = field
+            .ident
+            .as_ref()
+            .map_or_else(|| index.to_string(), |ident| ident.to_string());
+        let mut ty = &field.ty;
+        while let Type::Reference(reference) = ty {
+            ty = &reference.elem;
+        }
+        let segments = match ty {
+            Type::Path(type_path) => Some(path_segments(&type_path.path)),
+            _ => None,
+        };
+        pending.push((
+            module_path.to_string(),
+            format!("{}::{}", owner, name),
+            segments,
+        ));
+    }
+}
+
+/// The identifiers of a path, e.g. `["crate", "utils", "helper"]`.
+pub fn path_segments(path: &Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+/// Renders a visibility as written in source, e.g. `pub(crate)` or
+/// `pub(in crate::utils)`, or `private` when omitted.
+pub fn visibility_text(vis: &Visibility) -> String {
+    match vis {
+        Visibility::Public(_) => "pub".to_string(),
+        Visibility::Restricted(restricted) => {
+            let path = restricted
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            match restricted.in_token {
+                Some(_) => format!("pub(in {})", path),
+                None => format!("pub({})", path),
+            }
+        }
+        Visibility::Inherited => "private".to_string(),
+    }
+}
+
+/// Builds the table for `code` as the root file, `lib.rs`, of a crate named
+/// `c` of the given kind.
+#[cfg(test)]
+pub(crate) fn test_table(code: &str, crate_kind: crate::crate_tree::TargetKind) -> SymbolTable {
+    let source = SourceFile {
+        path: std::path::PathBuf::from("lib.rs"),
+        crate_name: "c".to_string(),
+        crate_kind,
+        module_path: "c".to_string(),
+        parent_module: None,
+        ast: syn::parse_file(code).expect("test code should parse"),
+    };
+    SymbolTable::build([&source])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crate_tree::TargetKind;
+
+    fn segments(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_string).collect()
+    }
+
+    fn item(path: &str, kind: ItemKind) -> Resolution {
+        Resolution::Item {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn keeps_same_named_items_apart() {
+        let symbols = test_table(
+            "
+            mod a { pub fn run() {} }
+            mod b { pub fn run() {} }
+            ",
+            TargetKind::Lib,
+        );
+        assert_eq!(
+            symbols.resolve("c", &segments("a::run")),
+            item("c::a::run", ItemKind::Function)
+        );
+        assert_eq!(
+            symbols.resolve("c::a", &segments("super::b::run")),
+            item("c::b::run", ItemKind::Function)
+        );
+    }
+
+    #[test]
+    fn identifies_inherent_and_trait_impl_methods() {
+        let symbols = test_table(
+            "
+            use std::fmt;
+            struct User;
+            impl User { fn new() -> User { User } }
+            impl fmt::Display for User { fn fmt(&self) {} }
+            impl fmt::Debug for User { fn fmt(&self) {} }
+            trait Shape { fn area(&self) -> f64; }
+            ",
+            TargetKind::Lib,
+        );
+        assert_eq!(symbols.method("c::User", "new"), Some("c::User::new"));
+        assert_eq!(symbols.method("c::Shape", "area"), Some("c::Shape::area"));
+        assert_eq!(
+            method_id("c::User", Some("Display"), "fmt"),
+            "<c::User as Display>::fmt"
+        );
+        assert_ne!(
+            method_id("c::User", Some("Display"), "fmt"),
+            method_id("c::User", Some("Debug"), "fmt")
+        );
+    }
+
+    #[test]
+    fn resolves_variants_only_when_they_exist() {
+        let symbols = test_table("enum Status { Active }", TargetKind::Lib);
+        assert_eq!(
+            symbols.resolve("c", &segments("Status::Active")),
+            Resolution::Variant {
+                enum_path: "c::Status".to_string(),
+                name: "Active".to_string(),
+            }
+        );
+        assert!(symbols.is_variant("c::Status::Active"));
+        assert!(!symbols.is_variant("c::Status::new"));
+        assert!(symbols.is_variant_name("Active"));
+        assert!(!symbols.is_variant_name("Status"));
+        assert!(!matches!(
+            symbols.resolve("c", &segments("Status::new")),
+            Resolution::Variant { .. }
+        ));
+    }
+
+    #[test]
+    fn places_exported_macros_at_the_crate_root() {
+        let symbols = test_table(
+            "
+            mod util {
+                #[macro_export]
+                macro_rules! log { () => {} }
+                macro_rules! local { () => {} }
+                fn f() {}
+            }
+            ",
+            TargetKind::Lib,
+        );
+        assert_eq!(
+            symbols.find_macro("c", &segments("log")),
+            Some("c::log".to_string())
+        );
+        assert_eq!(
+            symbols.find_macro("c::util", &segments("local")),
+            Some("c::util::local".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_names_in_order_of_precedence() {
+        let symbols = test_table(
+            "
+            mod a { pub fn run() {} pub fn stop() {} }
+            mod b { pub fn run() {} }
+            enum Mode { Fast }
+            mod local {
+                use super::a::run;
+                fn run() {}
+            }
+            mod explicit {
+                use super::a::*;
+                use super::b::run;
+            }
+            mod aliased {
+                use super::a::run as go;
+                use super::Mode::*;
+            }
+            ",
+            TargetKind::Lib,
+        );
+        // A module's own items come before its imports, and explicit
+        // imports before globs.
+        assert_eq!(
+            symbols.resolve("c::local", &segments("run")),
+            item("c::local::run", ItemKind::Function)
+        );
+        assert_eq!(
+            symbols.resolve("c::explicit", &segments("run")),
+            item("c::b::run", ItemKind::Function)
+        );
+        assert_eq!(
+            symbols.resolve("c::explicit", &segments("stop")),
+            item("c::a::stop", ItemKind::Function)
+        );
+        assert_eq!(
+            symbols.resolve("c::aliased", &segments("go")),
+            item("c::a::run", ItemKind::Function)
+        );
+        assert_eq!(
+            symbols.resolve("c::aliased", &segments("Fast")),
+            Resolution::Variant {
+                enum_path: "c::Mode".to_string(),
+                name: "Fast".to_string(),
+            }
+        );
+        // Only named or glob imports may bring a name in.
+        assert!(symbols.may_import("c::local", "run"));
+        assert!(!symbols.may_import("c::local", "stop"));
+        assert!(symbols.may_import("c::aliased", "Fast"));
+        assert!(!symbols.may_import("c::a", "run"));
+    }
+
+    #[test]
+    fn brings_parent_imports_into_scope_with_super_glob() {
+        let symbols = test_table(
+            "
+            mod a { pub fn run() {} }
+            use a::run;
+            mod tests {
+                use super::*;
+            }
+            ",
+            TargetKind::Lib,
+        );
+        assert_eq!(
+            symbols.resolve("c::tests", &segments("run")),
+            item("c::a::run", ItemKind::Function)
+        );
+    }
+
+    #[test]
+    fn follows_cycles_of_glob_imports_once() {
+        // Each module globs the root, which globs every module back. Without
+        // remembering what was looked up, this takes exponential time.
+        let code: String = (0..12)
+            .map(|i| {
+                format!("pub use m{i}::*; pub mod m{i} {{ use super::*; pub fn f{i}() {{}} }}")
+            })
+            .collect();
+        let symbols = test_table(&code, TargetKind::Lib);
+        assert_eq!(
+            symbols.resolve("c::m0", &segments("f11")),
+            item("c::m11::f11", ItemKind::Function)
+        );
+        assert!(!symbols.is_in_scope("c::m0", "missing"));
+        assert!(symbols.is_effectively_public("c::m5::f5"));
+    }
+
+    #[test]
+    fn knows_prelude_names_but_not_unknown_ones() {
+        let symbols = test_table("fn run() {}", TargetKind::Lib);
+        assert!(symbols.is_in_scope("c", "run"));
+        assert!(symbols.is_in_scope("c", "Some"));
+        assert!(!symbols.is_in_scope("c", "missing"));
+        assert_eq!(
+            symbols.resolve("c", &segments("Vec::new")),
+            Resolution::External("std::vec::Vec::new".to_string())
+        );
+    }
+
+    #[test]
+    fn reaches_public_items_only_through_public_modules() {
+        let symbols = test_table(
+            "
+            pub mod open {
+                pub fn visible() {}
+                pub(crate) fn limited() {}
+                fn secret() {}
+                pub mod inner { pub fn deep() {} }
+            }
+            mod closed { pub fn hidden() {} }
+            ",
+            TargetKind::Lib,
+        );
+        assert!(symbols.is_effectively_public("c"));
+        assert!(symbols.is_effectively_public("c::open::visible"));
+        assert!(symbols.is_effectively_public("c::open::inner::deep"));
+        assert!(!symbols.is_effectively_public("c::open::limited"));
+        assert!(!symbols.is_effectively_public("c::open::secret"));
+        assert!(!symbols.is_effectively_public("c::closed"));
+        assert!(!symbols.is_effectively_public("c::closed::hidden"));
+    }
+
+    #[test]
+    fn reaches_items_through_chained_and_glob_reexports() {
+        let symbols = test_table(
+            "
+            mod private {
+                pub fn run() {}
+                pub(crate) fn internal() {}
+                pub mod nested { pub fn deep() {} }
+                pub mod globbed { pub fn one() {} pub(crate) fn two() {} }
+            }
+            mod middle {
+                pub use crate::private::run;
+                pub use crate::private::nested;
+            }
+            pub mod api {
+                pub use crate::middle::run;
+                pub use crate::middle::nested;
+                pub use crate::private::globbed::*;
+            }
+            mod hidden { pub use crate::private::globbed; }
+            ",
+            TargetKind::Lib,
+        );
+        // `run` is re-exported by a private module, then again by a public one.
+        assert!(symbols.is_effectively_public("c::private::run"));
+        // A re-exported module makes its own `pub` items reachable.
+        assert!(symbols.is_effectively_public("c::private::nested"));
+        assert!(symbols.is_effectively_public("c::private::nested::deep"));
+        // A glob re-exports only the `pub` items inside.
+        assert!(symbols.is_effectively_public("c::private::globbed::one"));
+        assert!(!symbols.is_effectively_public("c::private::globbed::two"));
+        assert!(!symbols.is_effectively_public("c::private::globbed"));
+        assert!(!symbols.is_effectively_public("c::private::internal"));
+    }
+
+    #[test]
+    fn does_not_reach_items_of_binary_crates() {
+        let symbols = test_table(
+            "pub fn helper() {} pub mod api { pub fn run() {} }",
+            TargetKind::Bin,
+        );
+        assert!(!symbols.is_effectively_public("c"));
+        assert!(!symbols.is_effectively_public("c::helper"));
+        assert!(!symbols.is_effectively_public("c::api::run"));
+    }
+}